@@ -1,22 +1,25 @@
 #include <Nuance>
 
-#define MAX_ITER floor(fTime)
-
 layout(params) uniform Params {
+    layout(min = 1, max = 500, init = 50) uint maxIter;
     bool showColor;
+    layout(log, min = 1, max = 10000, init = 1) float zoom;
 };
 
 void main() {
     vec2 pos = mix(vec2(-2.5, -1), vec2(1.0, 1.0), fragCoord.xy / uResolution);
+    // Zoom on the seahorse valley
+    vec2 center = vec2(-0.75, 0.1);
+    pos = center + (pos - center) / zoom;
     vec2 c = vec2(0);
     uint iter = 0;
-    while (dot(c, c) <= 4 && iter < MAX_ITER) {
+    while (dot(c, c) <= 4 && iter < maxIter) {
         float temp = c.x * c.x - c.y * c.y + pos.x;
         c.y = 2 * c.x * c.y + pos.y;
         c.x = temp;
         iter += 1;
     }
-    float color = 1 - iter / MAX_ITER;
+    float color = 1 - float(iter) / maxIter;
     if (showColor) {
         fragColor = vec4(normalize(c), color, 1.0);
     } else {
@@ -0,0 +1,15 @@
+#include <Nuance>
+
+layout(params) uniform Params {
+    /// Color of the rings from the center to the border
+    layout(gradient, init = "0 #000000, 0.6 #ff4000, 1 #ffffc0") vec3 heat;
+    /// Brightness along the rings
+    layout(curve, min = 0, max = 2, init = "0 1, 0.5 2, 1 1") float falloff;
+    layout(min = 1, max = 20, init = 6) float rings;
+};
+
+void main() {
+    vec2 uv = (fragCoord.xy - vec2(uResolution) * 0.5) / float(uResolution.y);
+    float t = fract(length(uv) * rings - fTime * 0.2);
+    fragColor = vec4(heat(t) * falloff(fragCoordNorm.x), 1.0);
+}
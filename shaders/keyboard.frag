@@ -0,0 +1,13 @@
+#include <Nuance>
+
+void main() {
+    // Arrows move the color, space inverts it
+    vec2 st = fragCoordNorm;
+    vec3 color = vec3(0.2);
+    if (isKeyDown(KEY_LEFT) && st.x < 0.5) color.r = 1.0;
+    if (isKeyDown(KEY_RIGHT) && st.x >= 0.5) color.g = 1.0;
+    if (isKeyDown(KEY_UP) && st.y < 0.5) color.b = 1.0;
+    if (isKeyDown(KEY_DOWN) && st.y >= 0.5) color.rg = vec2(1.0);
+    if (isKeyToggled(KEY_SPACE)) color = 1.0 - color;
+    fragColor = vec4(color, 1.0);
+}
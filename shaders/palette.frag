@@ -0,0 +1,27 @@
+#include <Nuance>
+
+#define COLORS 5
+
+layout(params) uniform Params {
+    layout(color, init = vec3(0.5)) vec3 palette[COLORS];
+    layout(min = 0, max = 1, init = 1) float weights[COLORS];
+    layout(min = 0, max = 4, init = 1) float speed;
+};
+
+void main() {
+    float total = 0.0;
+    for (int i = 0; i < COLORS; i++) {
+        total += weights[i];
+    }
+    // Stripes sized by their weight, scrolling with time
+    float x = fract(fragCoordNorm.x + fTime * speed * 0.1) * total;
+    vec3 color = palette[COLORS - 1];
+    for (int i = 0; i < COLORS; i++) {
+        if (x < weights[i]) {
+            color = palette[i];
+            break;
+        }
+        x -= weights[i];
+    }
+    fragColor = vec4(color, 1.0);
+}
@@ -0,0 +1,22 @@
+[[block]]
+struct Params {
+    // @param color init=0.2,0.4,1
+    tint: vec3<f32>;
+    // @param min=4 max=128 init=32
+    cell_size: f32;
+    // @param checkbox init=1
+    checker: u32;
+};
+
+[[group(1), binding(0)]]
+var<uniform> params: Params;
+
+[[stage(fragment)]]
+fn main([[builtin(position)]] coord: vec4<f32>) -> [[location(0)]] vec4<f32> {
+    let cell = floor(coord.xy / params.cell_size);
+    var shade: f32 = 1.0;
+    if (params.checker == 1u && (i32(cell.x + cell.y) % 2) == 0) {
+        shade = 0.5;
+    }
+    return vec4<f32>(params.tint * shade, 1.0);
+}
@@ -4,17 +4,20 @@
 
 layout(params) uniform Params {
     layout(color, init = vec3(0.0, 0.0, 1)) vec3 rgb;
+    layout(color, init = vec4(1.0, 0.0, 0.0, 0.5)) vec4 tint;
     layout(min = 0, max = 1) float a;
-    vec3 pos;
-    vec2 b;
+    layout(min = 0, max = vec2(1.0, 2.0)) vec2 b;
     bool c;
+    /// Center of the circle, in pixels
+    layout(group = "Circle", label = "center", xy, canvas, max = vec2(1920, 1080)) vec2 pos;
+    layout(group = "Circle", init = mat2(1.0)) mat2 warp;
 };
 
 void main() {
-    float r = length(fragCoord.xy - pos.xy);
+    float r = length(warp * (fragCoord.xy - pos.xy));
     if (r <= 60 * (fMouseWheel + 1.0)) {
         fragColor = vec4(c ? 1.0 : 0.0, a, 0.0, 1.0);
     } else {
-        fragColor = vec4(rgb, 1.0);
+        fragColor = vec4(mix(rgb, tint.rgb, tint.a), 1.0);
     }
 }
@@ -13,6 +13,10 @@ layout(location = 0) out vec4 fragColor;
 layout(set = 0, binding = 0) uniform texture2D lastFrame;
 layout(set = 0, binding = 1) uniform sampler lastFrameSampler;
 
+// Keyboard state, a row for keys held down, pressed this frame and toggled
+// The x coordinate is the javascript key code like on Shadertoy
+layout(set = 3, binding = 0) uniform texture2D nuanceKeyboard;
+
 // Globals are variables your shader can access
 layout(push_constant) uniform Globals {
 // Window resolution
@@ -27,10 +31,95 @@ layout(push_constant) uniform Globals {
     float fTime;
 // The number of frame we're at
     uint uFrame;
+// Like Shadertoy iMouse, xy : mouse position while the left button is down,
+// zw : position of the last click, z < 0 when released, w < 0 after the click frame
+    vec4 fMouseClick;
+// Mouse position in normalized space
+    vec2 fMouseNorm;
+// Mouse buttons down, left is 1, right 2 and middle 4
+    uint uMouseButtons;
+// Time since the last frame in sec
+    float fTimeDelta;
+// Smoothed number of frames per sec
+    float fFrameRate;
+// Local date : year, month, day and seconds since midnight
+    vec4 uDate;
 };
 
 #define FIRST_RUN uFrame == 0
 
+// Mouse buttons for uMouseButtons
+#define MOUSE_LEFT 1u
+#define MOUSE_RIGHT 2u
+#define MOUSE_MIDDLE 4u
+
+// Key codes for the keyboard functions
+#define KEY_A 65
+#define KEY_B 66
+#define KEY_C 67
+#define KEY_D 68
+#define KEY_E 69
+#define KEY_F 70
+#define KEY_G 71
+#define KEY_H 72
+#define KEY_I 73
+#define KEY_J 74
+#define KEY_K 75
+#define KEY_L 76
+#define KEY_M 77
+#define KEY_N 78
+#define KEY_O 79
+#define KEY_P 80
+#define KEY_Q 81
+#define KEY_R 82
+#define KEY_S 83
+#define KEY_T 84
+#define KEY_U 85
+#define KEY_V 86
+#define KEY_W 87
+#define KEY_X 88
+#define KEY_Y 89
+#define KEY_Z 90
+#define KEY_0 48
+#define KEY_1 49
+#define KEY_2 50
+#define KEY_3 51
+#define KEY_4 52
+#define KEY_5 53
+#define KEY_6 54
+#define KEY_7 55
+#define KEY_8 56
+#define KEY_9 57
+#define KEY_SPACE 32
+#define KEY_ENTER 13
+#define KEY_ESCAPE 27
+#define KEY_TAB 9
+#define KEY_BACKSPACE 8
+#define KEY_LEFT 37
+#define KEY_UP 38
+#define KEY_RIGHT 39
+#define KEY_DOWN 40
+#define KEY_SHIFT 16
+#define KEY_CONTROL 17
+#define KEY_ALT 18
+#define KEY_PAGE_UP 33
+#define KEY_PAGE_DOWN 34
+#define KEY_END 35
+#define KEY_HOME 36
+#define KEY_INSERT 45
+#define KEY_DELETE 46
+#define KEY_F2 113
+#define KEY_F3 114
+#define KEY_F4 115
+#define KEY_F5 116
+#define KEY_F6 117
+#define KEY_F7 118
+#define KEY_F8 119
+#define KEY_F9 120
+#define KEY_F10 121
+#define KEY_F11 122
+#define KEY_F12 123
+
 // Generate a pseudo random value from a vec2
 // Source : https://thebookofshaders.com/10/
 float noise(vec2 st) {
@@ -51,3 +140,33 @@ vec4 samplePrevious(vec2 xy) {
 vec4 samplePrevious() {
     return samplePreviousN(fragCoordNorm);
 }
+
+// Rotation matrix from euler angles in radians, rotates around x, then y, then z
+mat3 eulerRotation(vec3 angles) {
+    vec3 s = sin(angles);
+    vec3 c = cos(angles);
+    mat3 rx = mat3(1.0, 0.0, 0.0, 0.0, c.x, s.x, 0.0, -s.x, c.x);
+    mat3 ry = mat3(c.y, 0.0, -s.y, 0.0, 1.0, 0.0, s.y, 0.0, c.y);
+    mat3 rz = mat3(c.z, s.z, 0.0, -s.z, c.z, 0.0, 0.0, 0.0, 1.0);
+    return rz * ry * rx;
+}
+
+// State of a key in a row of the keyboard texture
+bool keyState(int key, int row) {
+    return texelFetch(sampler2D(nuanceKeyboard, lastFrameSampler), ivec2(key, row), 0).x > 0.5;
+}
+
+// Is the key held down
+bool isKeyDown(int key) {
+    return keyState(key, 0);
+}
+
+// Has the key been pressed this frame
+bool isKeyPressed(int key) {
+    return keyState(key, 1);
+}
+
+// Is the key toggled, switches on every press
+bool isKeyToggled(int key) {
+    return keyState(key, 2);
+}
@@ -1,17 +1,25 @@
+use std::convert::TryFrom;
 use std::mem;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use egui::special_emojis::GITHUB;
-use egui::{ClippedMesh, Color32, CtxRef, DragValue, Frame, Id, Texture, TextureId, Ui};
+use egui::{
+    pos2, ClippedMesh, Color32, CtxRef, DragValue, Frame, Id, Rgba, Sense, Texture, TextureId, Ui,
+};
 use egui_wgpu_backend::ScreenDescriptor;
 use egui_winit_platform::Platform;
 use image::ImageFormat;
 use log::debug;
+use mint::{Vector2, Vector3};
 use winit::event::Event;
 use winit::event_loop::EventLoopProxy;
 
-use crate::shader::Slider;
+use crate::input;
+use crate::lfo::{Lfo, Waveform};
+use crate::presets::Preset;
+use crate::shader::{sample_curve, sample_gradient, GradientStop, Param, ShaderMetadata, Slider};
+use crate::timeline::Interpolation;
 use crate::{Command, Nuance};
 
 pub struct Gui {
@@ -21,7 +29,33 @@ pub struct Gui {
     pub ui_width: u32,
     /// true if the profiling window should be open
     pub profiling_window: bool,
+    /// The pointer is over the canvas and not over a window
+    pub canvas_hovered: bool,
     export_window: bool,
+    timeline_window: bool,
+    /// Param index and keyframe index of the keyframe edited in the timeline
+    selected_keyframe: Option<(usize, usize)>,
+    /// Index of the last applied preset
+    selected_preset: Option<usize>,
+    /// Name being edited for the selected preset
+    preset_name: String,
+    morph: Morph,
+    /// Seed of the next randomize or mutate
+    seed: u32,
+    /// Fraction of the range a mutation moves values
+    mutate_amount: f32,
+}
+
+/// Blend between two presets
+struct Morph {
+    /// Preset indices
+    from: usize,
+    to: usize,
+    amount: f32,
+    /// Duration of a played transition in sec
+    duration: f32,
+    /// When the played transition started
+    start: Option<Instant>,
 }
 
 impl Gui {
@@ -30,7 +64,21 @@ impl Gui {
             egui_platform,
             ui_width,
             profiling_window: false,
+            canvas_hovered: false,
             export_window: false,
+            timeline_window: false,
+            selected_keyframe: None,
+            selected_preset: None,
+            preset_name: String::new(),
+            morph: Morph {
+                from: 0,
+                to: 1,
+                amount: 0.0,
+                duration: 2.0,
+                start: None,
+            },
+            seed: 0,
+            mutate_amount: 0.1,
         }
     }
 
@@ -65,8 +113,19 @@ impl Gui {
                 "mouse : ({:.0}, {:.0}) px",
                 app.globals.mouse.x, app.globals.mouse.y
             ));
+            ui.label(format!(
+                "click : ({:.0}, {:.0}) px, buttons : {}",
+                app.globals.mouse_click.z.abs(),
+                app.globals.mouse_click.w.abs(),
+                app.globals.mouse_buttons
+            ));
             ui.label(format!("mouse wheel : {:.1}", app.globals.mouse_wheel));
             ui.label(format!("time : {:.3} s", app.globals.time));
+            ui.label(format!(
+                "delta : {:.1} ms ({:.0} fps)",
+                app.globals.time_delta * 1000.0,
+                app.globals.frame_rate
+            ));
             ui.label(format!("frame : {}", app.globals.frame));
 
             if ui.small_button("Reset").clicked() {
@@ -91,6 +150,7 @@ impl Gui {
                     .max_decimals(3)
                     .speed(0.01),
             );
+            ui.checkbox(&mut app.settings.preserve_params, "keep params on reload");
 
             ui.separator();
 
@@ -108,6 +168,9 @@ impl Gui {
                 if app.shader_loaded() && ui.button("Export").clicked() {
                     app.gui.export_window = true;
                 }
+                if app.shader_loaded() && ui.button("Timeline").clicked() {
+                    app.gui.timeline_window = true;
+                }
             });
 
             // Shader name
@@ -125,6 +188,13 @@ impl Gui {
                 }
             }
 
+            // Params can be keyframed while the timeline is open
+            let keyframe_time = if app.gui.timeline_window {
+                Some(app.timeline.local_time(app.globals.time))
+            } else {
+                None
+            };
+
             if let Some(Some(metadata)) = app.shader.as_mut().map(|it| it.metadata.as_mut()) {
                 ui.separator();
                 ui.horizontal(|ui| {
@@ -133,16 +203,68 @@ impl Gui {
                         proxy.send_event(Command::ResetParams).unwrap();
                     }
                 });
-                let sliders = &mut metadata.sliders;
-                egui::Grid::new("params grid")
-                    .striped(true)
-                    //.max_col_width(self.ui_width as f32 - 20.0)
-                    .show(ui, |ui| {
-                        for slider in sliders {
-                            slider.draw(ui);
-                            ui.end_row();
+                explore(ui, metadata, &mut app.gui.seed, &mut app.gui.mutate_amount);
+                edit_presets(
+                    ui,
+                    proxy,
+                    metadata,
+                    &mut app.gui.selected_preset,
+                    &mut app.gui.preset_name,
+                );
+                edit_morph(ui, metadata, &mut app.gui.morph);
+                // Consecutive params of the same group are displayed in their own section
+                let mut luts_edited = false;
+                let mut params = &mut metadata.params[..];
+                let mut grid = 0;
+                while let Some(first) = params.first() {
+                    let group = first.group.clone();
+                    let len = params
+                        .iter()
+                        .position(|it| it.group != group)
+                        .unwrap_or_else(|| params.len());
+                    let (section, rest) = mem::take(&mut params).split_at_mut(len);
+                    let draw_grid = |ui: &mut Ui| {
+                        egui::Grid::new(("params grid", grid))
+                            .striped(true)
+                            //.max_col_width(self.ui_width as f32 - 20.0)
+                            .show(ui, |ui| {
+                                for param in section {
+                                    luts_edited |= param.draw(ui);
+                                    if ui
+                                        .selectable_label(param.locked, "🔒")
+                                        .on_hover_text("Lock the value for randomize and mutate")
+                                        .clicked()
+                                    {
+                                        param.locked = !param.locked;
+                                    }
+                                    lfo_button(ui, param);
+                                    if let Some(time) = keyframe_time {
+                                        keyframe_button(ui, param, time);
+                                    }
+                                    ui.end_row();
+                                    // The oscillator settings go on their own row
+                                    if !param.lfos.is_empty() {
+                                        ui.label("");
+                                        edit_lfos(ui, param);
+                                        ui.end_row();
+                                    }
+                                }
+                            });
+                    };
+                    match group {
+                        Some(group) => {
+                            egui::CollapsingHeader::new(group)
+                                .default_open(true)
+                                .show(ui, draw_grid);
                         }
-                    });
+                        None => draw_grid(ui),
+                    }
+                    grid += 1;
+                    params = rest;
+                }
+                if luts_edited {
+                    metadata.invalidate_luts();
+                }
             }
 
             ui.add_space(ui.available_size().y - 2.0 * ui.spacing().item_spacing.y - 30.0);
@@ -160,7 +282,7 @@ impl Gui {
         egui::CentralPanel::default()
             .frame(Frame::none())
             .show(&app.gui.context(), |ui| {
-                ui.image(
+                let image = ui.image(
                     TextureId::User(0),
                     egui::Vec2::new(
                         window.physical_width as f32 / window.scale_factor
@@ -168,6 +290,22 @@ impl Gui {
                         window.physical_height as f32 / window.scale_factor,
                     ),
                 );
+
+                // Ctrl + drag on the canvas sets the param with the canvas setting
+                let canvas = ui.interact(image.rect, Id::new("canvas"), Sense::drag());
+                app.gui.canvas_hovered = canvas.hovered();
+                if ui.input().modifiers.ctrl {
+                    if let Some(pos) = canvas.interact_pointer_pos() {
+                        let rect = canvas.rect;
+                        let x = ((pos.x - rect.left()) / rect.width()).max(0.0).min(1.0);
+                        let y = ((pos.y - rect.top()) / rect.height()).max(0.0).min(1.0);
+                        if let Some(Some(metadata)) =
+                            app.shader.as_mut().map(|it| it.metadata.as_mut())
+                        {
+                            metadata.drag_on_canvas(x, y, app.globals.resolution);
+                        }
+                    }
+                }
             });
 
         let format_ref = &mut app.export_data.format;
@@ -204,6 +342,162 @@ impl Gui {
                 }
             });
 
+        let mut seek = None;
+        let time = app.timeline.local_time(app.globals.time);
+        let timeline = &mut app.timeline;
+        let selected = &mut app.gui.selected_keyframe;
+        let params = app
+            .shader
+            .as_mut()
+            .and_then(|it| it.metadata.as_mut())
+            .map(|it| &mut it.params);
+        egui::Window::new("Timeline")
+            .id(Id::new("timeline window"))
+            .open(&mut app.gui.timeline_window)
+            .collapsible(false)
+            .scroll(false)
+            .show(&app.gui.egui_platform.context(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        DragValue::new(&mut timeline.duration)
+                            .prefix("duration : ")
+                            .suffix(" s")
+                            .clamp_range(0.1..=3600.0)
+                            .max_decimals(2)
+                            .speed(0.1),
+                    );
+                    ui.checkbox(&mut timeline.looping, "loop");
+                    ui.label(format!("time : {:.2} s", time));
+                });
+
+                let duration = timeline.duration;
+                let width = 360.0;
+                let (rect, response) =
+                    ui.allocate_exact_size(egui::vec2(width, 16.0), Sense::click_and_drag());
+                // Click or drag on the ruler to move in time
+                if let Some(pos) = response.interact_pointer_pos() {
+                    seek = Some(track_time(rect, pos, duration));
+                }
+                draw_track(ui, rect, time, duration);
+
+                let params = match params {
+                    Some(params) => params,
+                    None => return,
+                };
+                if params.iter().all(|it| it.keyframes.is_empty()) {
+                    ui.label("Use the ◇ buttons next to the params to add keyframes");
+                    return;
+                }
+
+                egui::Grid::new("timeline grid").show(ui, |ui| {
+                    for (i, param) in params.iter_mut().enumerate() {
+                        if param.keyframes.is_empty() {
+                            continue;
+                        }
+                        ui.label(param.label());
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::vec2(width, 16.0), Sense::hover());
+                        draw_track(ui, rect, time, duration);
+
+                        let mut moved = None;
+                        for (j, keyframe) in param.keyframes.iter_mut().enumerate() {
+                            let x = rect.left() + keyframe.time / duration * rect.width();
+                            let center = pos2(x.min(rect.right()), rect.center().y);
+                            let handle =
+                                egui::Rect::from_center_size(center, egui::Vec2::splat(10.0));
+                            let response = ui.interact(
+                                handle,
+                                Id::new(("keyframe", i, j)),
+                                Sense::click_and_drag(),
+                            );
+                            if response.clicked() || response.drag_started() {
+                                *selected = Some((i, j));
+                            }
+                            if let Some(pos) = response
+                                .interact_pointer_pos()
+                                .filter(|_| response.dragged())
+                            {
+                                keyframe.time = track_time(rect, pos, duration);
+                                moved = Some(keyframe.time);
+                            }
+
+                            let color = if *selected == Some((i, j)) {
+                                ui.visuals().selection.bg_fill
+                            } else {
+                                ui.visuals().widgets.inactive.fg_stroke.color
+                            };
+                            let r = 5.0;
+                            ui.painter().add(egui::Shape::convex_polygon(
+                                vec![
+                                    pos2(center.x, center.y - r),
+                                    pos2(center.x + r, center.y),
+                                    pos2(center.x, center.y + r),
+                                    pos2(center.x - r, center.y),
+                                ],
+                                color,
+                                egui::Stroke::none(),
+                            ));
+                        }
+                        // Follow the dragged keyframe when it goes past another one
+                        if let Some(time) = moved {
+                            param.sort_keyframes();
+                            let j = param.keyframes.iter().position(|it| it.time == time);
+                            *selected = j.map(|j| (i, j));
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                let keyframes = selected
+                    .and_then(|(i, _)| params.get_mut(i))
+                    .map(|it| &mut it.keyframes);
+                if let (Some((_, j)), Some(keyframes)) = (*selected, keyframes) {
+                    if j >= keyframes.len() {
+                        return;
+                    }
+                    ui.separator();
+                    let mut delete = false;
+                    let mut moved = false;
+                    ui.horizontal(|ui| {
+                        let keyframe = &mut keyframes[j];
+                        moved = ui
+                            .add(
+                                DragValue::new(&mut keyframe.time)
+                                    .prefix("time : ")
+                                    .suffix(" s")
+                                    .clamp_range(0.0..=duration)
+                                    .max_decimals(2)
+                                    .speed(0.01),
+                            )
+                            .changed();
+                        egui::ComboBox::from_id_source("keyframe interpolation")
+                            .selected_text(keyframe.interpolation.name())
+                            .show_ui(ui, |ui| {
+                                for interpolation in Interpolation::ALL.iter() {
+                                    ui.selectable_value(
+                                        &mut keyframe.interpolation,
+                                        *interpolation,
+                                        interpolation.name(),
+                                    );
+                                }
+                            });
+                        delete = ui.button("Delete").clicked();
+                    });
+                    if delete {
+                        keyframes.remove(j);
+                        *selected = None;
+                    } else if moved {
+                        let time = keyframes[j].time;
+                        keyframes.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+                        let j = keyframes.iter().position(|it| it.time == time);
+                        *selected = selected.and_then(|(i, _)| j.map(|j| (i, j)));
+                    }
+                }
+            });
+        if let Some(time) = seek {
+            app.seek(time);
+        }
+
         if app.gui.profiling_window {
             app.gui.profiling_window = puffin_egui::profiler_window(&app.gui.context());
         }
@@ -225,55 +519,1008 @@ impl Gui {
     }
 }
 
+impl Param {
+    /// Draw the label and the slider on a grid row, true if a lookup texture was edited
+    pub fn draw(&mut self, ui: &mut Ui) -> bool {
+        let label = ui.label(self.label());
+        if let Some(description) = self.description.as_ref() {
+            label.on_hover_text(description);
+        }
+        self.slider.draw(ui)
+    }
+}
+
 impl Slider {
-    pub fn draw(&mut self, ui: &mut Ui) {
+    /// Draw the widget to edit the value, true if a lookup texture was edited
+    pub fn draw(&mut self, ui: &mut Ui) -> bool {
+        let mut snap = false;
         match self {
             Slider::Float {
-                name,
                 min,
                 max,
+                log,
                 value,
                 ..
             } => {
-                ui.label(name.as_str());
-                ui.add(
-                    DragValue::new(value)
-                        .clamp_range(*min..=*max)
-                        .speed((*max - *min) / ui.available_width())
-                        .max_decimals(3),
-                );
+                let changed = if *log {
+                    // A drag value moves linearly, a slider can map its range exponentially
+                    ui.add(
+                        egui::Slider::new(value, *min..=*max)
+                            .logarithmic(true)
+                            .max_decimals(3),
+                    )
+                    .changed()
+                } else {
+                    ui.add(
+                        DragValue::new(value)
+                            .clamp_range(*min..=*max)
+                            .speed((*max - *min) / ui.available_width())
+                            .max_decimals(3),
+                    )
+                    .changed()
+                };
+                snap = changed;
             }
-            Slider::Vec2 { name, value, .. } => {
-                ui.label(name.as_str());
-                ui.spacing_mut().item_spacing.x = 2.0;
-                ui.columns(2, |columns| {
-                    columns[0].add(DragValue::new(&mut value.x).speed(0.01).max_decimals(3));
-                    columns[1].add(DragValue::new(&mut value.y).speed(0.01).max_decimals(3));
+            Slider::Int {
+                min, max, value, ..
+            } => {
+                let mut values = [*value];
+                if drag_int_vector(ui, &mut values, &[*min], &[*max]) {
+                    *value = values[0];
+                    snap = true;
+                }
+            }
+            Slider::UInt {
+                min, max, value, ..
+            } => {
+                let mut values = [*value];
+                if drag_int_vector(ui, &mut values, &[*min], &[*max]) {
+                    *value = values[0];
+                    snap = true;
+                }
+            }
+            Slider::Vec2 {
+                min,
+                max,
+                speed,
+                value,
+                ..
+            } => {
+                let mut values: [f32; 2] = (*value).into();
+                if drag_vector(ui, &mut values, min.as_ref(), max.as_ref(), speed.as_ref()) {
+                    *value = values.into();
+                }
+            }
+            Slider::Pad {
+                min,
+                max,
+                speed,
+                value,
+                ..
+            } => {
+                ui.vertical(|ui| {
+                    let size = ui.available_width().min(120.0);
+                    let (rect, response) =
+                        ui.allocate_exact_size(egui::Vec2::splat(size), Sense::click_and_drag());
+                    // Both axis go down like the canvas
+                    if let Some(pos) = response.interact_pointer_pos() {
+                        let x = ((pos.x - rect.left()) / rect.width()).max(0.0).min(1.0);
+                        let y = ((pos.y - rect.top()) / rect.height()).max(0.0).min(1.0);
+                        value.x = min.x + x * (max.x - min.x);
+                        value.y = min.y + y * (max.y - min.y);
+                    }
+
+                    let x = ((value.x - min.x) / (max.x - min.x)).max(0.0).min(1.0);
+                    let y = ((value.y - min.y) / (max.y - min.y)).max(0.0).min(1.0);
+                    let point = pos2(
+                        rect.left() + x * rect.width(),
+                        rect.top() + y * rect.height(),
+                    );
+                    let painter = ui.painter();
+                    let stroke = ui.visuals().widgets.inactive.fg_stroke;
+                    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+                    painter.line_segment(
+                        [pos2(rect.left(), point.y), pos2(rect.right(), point.y)],
+                        stroke,
+                    );
+                    painter.line_segment(
+                        [pos2(point.x, rect.top()), pos2(point.x, rect.bottom())],
+                        stroke,
+                    );
+                    painter.circle_filled(point, 4.0, ui.visuals().selection.bg_fill);
+
+                    let mut values: [f32; 2] = (*value).into();
+                    if drag_vector(ui, &mut values, min.as_ref(), max.as_ref(), speed.as_ref()) {
+                        *value = values.into();
+                    }
+                });
+            }
+            Slider::Vec3 {
+                min,
+                max,
+                speed,
+                value,
+                ..
+            } => {
+                let mut values: [f32; 3] = (*value).into();
+                if drag_vector(ui, &mut values, min.as_ref(), max.as_ref(), speed.as_ref()) {
+                    *value = values.into();
+                }
+            }
+            Slider::Direction { value, .. } => {
+                ui.horizontal(|ui| {
+                    drag_direction(ui, value);
+                    // Angles are easier to set precisely
+                    let (mut azimuth, mut elevation) = to_spherical(value);
+                    let mut changed = false;
+                    ui.vertical(|ui| {
+                        changed |= ui
+                            .add(
+                                DragValue::new(&mut azimuth)
+                                    .prefix("azimuth : ")
+                                    .suffix("°")
+                                    .clamp_range(-180.0..=180.0)
+                                    .max_decimals(1),
+                            )
+                            .changed();
+                        changed |= ui
+                            .add(
+                                DragValue::new(&mut elevation)
+                                    .prefix("elevation : ")
+                                    .suffix("°")
+                                    .clamp_range(-90.0..=90.0)
+                                    .max_decimals(1),
+                            )
+                            .changed();
+                    });
+                    if changed {
+                        *value = from_spherical(azimuth, elevation);
+                    }
                 });
             }
-            Slider::Vec3 { name, value, .. } => {
-                ui.label(name.as_str());
+            Slider::Euler { value, .. } => {
+                let mut degrees = [
+                    value.x.to_degrees(),
+                    value.y.to_degrees(),
+                    value.z.to_degrees(),
+                ];
                 ui.spacing_mut().item_spacing.x = 2.0;
+                let mut changed = false;
                 ui.columns(3, |columns| {
-                    columns[0].add(DragValue::new(&mut value.x).speed(0.01).max_decimals(3));
-                    columns[1].add(DragValue::new(&mut value.y).speed(0.01).max_decimals(3));
-                    columns[2].add(DragValue::new(&mut value.z).speed(0.01).max_decimals(3));
+                    for (column, angle) in columns.iter_mut().zip(degrees.iter_mut()) {
+                        changed |= column
+                            .add(DragValue::new(angle).suffix("°").max_decimals(1))
+                            .changed();
+                    }
                 });
+                if changed {
+                    // Wrap around to stay in ]-180, 180]
+                    let wrap = |it: f32| (180.0 - (180.0 - it).rem_euclid(360.0)).to_radians();
+                    *value = Vector3::from([wrap(degrees[0]), wrap(degrees[1]), wrap(degrees[2])]);
+                }
             }
-            Slider::Color { name, value, .. } => {
-                ui.label(name.as_str());
+            Slider::Color { value, .. } => {
                 // I feel bad for doing this BUT mint only implements AsRef but not AsMut,
                 // so this right here is the same implementation as AsRef but mutable
                 let ref_mut = unsafe { mem::transmute(value) };
                 ui.color_edit_button_rgb(ref_mut);
             }
-            Slider::Bool { name, value, .. } => {
-                ui.label(name.as_str());
+            Slider::Vec4 {
+                min,
+                max,
+                speed,
+                value,
+                ..
+            } => {
+                let mut values: [f32; 4] = (*value).into();
+                if drag_vector(ui, &mut values, min.as_ref(), max.as_ref(), speed.as_ref()) {
+                    *value = values.into();
+                }
+            }
+            Slider::ColorAlpha { value, .. } => {
+                // Same as above
+                let ref_mut = unsafe { mem::transmute(value) };
+                ui.color_edit_button_rgba_unmultiplied(ref_mut);
+            }
+            Slider::IVec2 {
+                min, max, value, ..
+            } => {
+                let mut values: [i32; 2] = (*value).into();
+                if drag_int_vector(ui, &mut values, min.as_ref(), max.as_ref()) {
+                    *value = values.into();
+                    snap = true;
+                }
+            }
+            Slider::IVec3 {
+                min, max, value, ..
+            } => {
+                let mut values: [i32; 3] = (*value).into();
+                if drag_int_vector(ui, &mut values, min.as_ref(), max.as_ref()) {
+                    *value = values.into();
+                    snap = true;
+                }
+            }
+            Slider::UVec2 {
+                min, max, value, ..
+            } => {
+                let mut values: [u32; 2] = (*value).into();
+                if drag_int_vector(ui, &mut values, min.as_ref(), max.as_ref()) {
+                    *value = values.into();
+                    snap = true;
+                }
+            }
+            Slider::UVec3 {
+                min, max, value, ..
+            } => {
+                let mut values: [u32; 3] = (*value).into();
+                if drag_int_vector(ui, &mut values, min.as_ref(), max.as_ref()) {
+                    *value = values.into();
+                    snap = true;
+                }
+            }
+            Slider::Mat2 { value, .. } => {
+                let mut columns: [[f32; 2]; 2] = (*value).into();
+                ui.vertical(|ui| {
+                    let mut changed = drag_matrix(ui, &mut columns);
+                    ui.collapsing("rotation / scale", |ui| {
+                        let [x, y] = &mut columns;
+                        changed |= drag_rotation_scale(ui, x, y);
+                    });
+                    if changed {
+                        *value = columns.into();
+                    }
+                });
+            }
+            Slider::Mat3 { value, .. } => {
+                let mut columns: [[f32; 3]; 3] = (*value).into();
+                ui.vertical(|ui| {
+                    let mut changed = drag_matrix(ui, &mut columns);
+                    // Only the 2d linear part, the last column is the translation
+                    ui.collapsing("rotation / scale", |ui| {
+                        let [x, y, _] = &mut columns;
+                        changed |= drag_rotation_scale(ui, x, y);
+                    });
+                    if changed {
+                        *value = columns.into();
+                    }
+                });
+            }
+            Slider::Mat4 { value, .. } => {
+                let mut columns: [[f32; 4]; 4] = (*value).into();
+                ui.vertical(|ui| {
+                    if drag_matrix(ui, &mut columns) {
+                        *value = columns.into();
+                    }
+                });
+            }
+            Slider::Bool { key, value, .. } => {
                 let mut val = *value != 0;
-                if ui.checkbox(&mut val, "").changed() {
+                // Show the bound key next to the checkbox
+                let text = key.map_or(String::new(), |it| {
+                    format!(
+                        "{} ({})",
+                        input::key_name(it.key).unwrap_or("?"),
+                        if it.hold { "hold" } else { "toggle" }
+                    )
+                });
+                if ui.checkbox(&mut val, text).changed() {
                     *value = if val { 1 } else { 0 };
                 }
             }
+            Slider::Enum {
+                name,
+                options,
+                value,
+                ..
+            } => {
+                egui::ComboBox::from_id_source(name.as_str())
+                    .selected_text(options.get(*value as usize).map_or("", String::as_str))
+                    .show_ui(ui, |ui| {
+                        for (i, option) in options.iter().enumerate() {
+                            ui.selectable_value(value, i as i32, option);
+                        }
+                    });
+            }
+            Slider::Array { name, items } => {
+                egui::CollapsingHeader::new(format!("{} items", items.len()))
+                    .id_source(name.as_str())
+                    .show(ui, |ui| {
+                        egui::Grid::new(name.as_str()).striped(true).show(ui, |ui| {
+                            for item in items.iter_mut() {
+                                ui.label(item.name());
+                                item.draw(ui);
+                                ui.end_row();
+                            }
+                        });
+                    });
+            }
+            Slider::Gradient { alpha, value, .. } => {
+                return ui.vertical(|ui| edit_gradient(ui, *alpha, value)).inner;
+            }
+            Slider::Curve {
+                min, max, value, ..
+            } => {
+                return ui.vertical(|ui| edit_curve(ui, *min, *max, value)).inner;
+            }
+        }
+        // Values are snapped to their step when set
+        if snap {
+            if let Some(components) = self.components() {
+                self.set_components(&components);
+            }
+        }
+        false
+    }
+}
+
+/// Apply, create, rename, update and delete the presets of the shader
+fn edit_presets(
+    ui: &mut Ui,
+    proxy: &EventLoopProxy<Command>,
+    metadata: &mut ShaderMetadata,
+    selected: &mut Option<usize>,
+    name: &mut String,
+) {
+    // The presets may have changed on reload
+    *selected = selected.filter(|it| *it < metadata.presets.len());
+    // Saving would overwrite the presets that couldn't be read
+    if let Some(error) = metadata.presets_error.as_ref() {
+        ui.colored_label(Color32::RED, format!("× Presets not loaded : {}", error));
+        return;
+    }
+
+    ui.horizontal(|ui| {
+        let text = selected.map_or("no preset", |it| metadata.presets[it].name.as_str());
+        let mut apply = None;
+        egui::ComboBox::from_id_source("presets")
+            .selected_text(text)
+            .show_ui(ui, |ui| {
+                for (i, preset) in metadata.presets.iter().enumerate() {
+                    if ui
+                        .selectable_label(*selected == Some(i), &preset.name)
+                        .clicked()
+                    {
+                        apply = Some(i);
+                    }
+                }
+            });
+        if let Some(i) = apply {
+            let preset = metadata.presets[i].clone();
+            metadata.set_values(&preset.values);
+            metadata.set_lfos(&preset.lfos);
+            *selected = Some(i);
+            *name = metadata.presets[i].name.clone();
+        }
+
+        if ui
+            .button("New")
+            .on_hover_text("Save the current values as a new preset")
+            .clicked()
+        {
+            let count = metadata.presets.len();
+            let preset_name = (1..)
+                .map(|n| format!("Preset {}", count + n))
+                .find(|it| metadata.presets.iter().all(|p| &p.name != it))
+                .unwrap();
+            let values = metadata.values();
+            let lfos = metadata.lfos();
+            metadata.presets.push(Preset {
+                name: preset_name.clone(),
+                values,
+                lfos,
+            });
+            *selected = Some(count);
+            *name = preset_name;
+            proxy.send_event(Command::SavePresets).unwrap();
+        }
+    });
+
+    if let Some(i) = *selected {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(name).desired_width(100.0));
+            if ui.button("Rename").clicked() && !name.is_empty() {
+                metadata.presets[i].name = name.clone();
+                proxy.send_event(Command::SavePresets).unwrap();
+            }
+            if ui
+                .button("Save")
+                .on_hover_text("Replace the preset with the current values")
+                .clicked()
+            {
+                metadata.presets[i].values = metadata.values();
+                metadata.presets[i].lfos = metadata.lfos();
+                proxy.send_event(Command::SavePresets).unwrap();
+            }
+            if ui.button("Delete").clicked() {
+                metadata.presets.remove(i);
+                *selected = None;
+                proxy.send_event(Command::SavePresets).unwrap();
+            }
+        });
+    }
+}
+
+/// Randomize and mutate the unlocked params. The seed advances after each use,
+/// set it back to replay an exploration.
+fn explore(ui: &mut Ui, metadata: &mut ShaderMetadata, seed: &mut u32, amount: &mut f32) {
+    ui.horizontal(|ui| {
+        if ui
+            .button("Randomize")
+            .on_hover_text("Random values within the bounds of each param")
+            .clicked()
+        {
+            metadata.randomize(*seed as u64);
+            *seed = seed.wrapping_add(1);
+        }
+        if ui
+            .button("Mutate")
+            .on_hover_text("Move the values randomly by a fraction of their range")
+            .clicked()
+        {
+            metadata.mutate(*seed as u64, *amount);
+            *seed = seed.wrapping_add(1);
+        }
+        ui.add(
+            DragValue::new(amount)
+                .clamp_range(0.0..=1.0)
+                .speed(0.005)
+                .max_decimals(3),
+        )
+        .on_hover_text("Mutation amount");
+        ui.add(DragValue::new(seed).prefix("seed : "));
+    });
+}
+
+/// Blend between two presets with a slider or over a duration
+fn edit_morph(ui: &mut Ui, metadata: &mut ShaderMetadata, morph: &mut Morph) {
+    let count = metadata.presets.len();
+    if count < 2 {
+        morph.start = None;
+        return;
+    }
+    morph.from = morph.from.min(count - 1);
+    morph.to = morph.to.min(count - 1);
+
+    let mut changed = false;
+    egui::CollapsingHeader::new("Morph")
+        .id_source("morph")
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                changed |= select_preset(ui, "morph from", &metadata.presets, &mut morph.from);
+                ui.label("→");
+                changed |= select_preset(ui, "morph to", &metadata.presets, &mut morph.to);
+            });
+            changed |= ui
+                .add(egui::Slider::new(&mut morph.amount, 0.0..=1.0).max_decimals(3))
+                .changed();
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut morph.duration)
+                        .prefix("duration : ")
+                        .suffix(" s")
+                        .clamp_range(0.1..=600.0)
+                        .max_decimals(1)
+                        .speed(0.1),
+                );
+                let text = if morph.start.is_some() {
+                    "Stop"
+                } else {
+                    "Play"
+                };
+                if ui.button(text).clicked() {
+                    morph.start = match morph.start {
+                        Some(_) => None,
+                        None => Some(Instant::now()),
+                    };
+                }
+            });
+        });
+
+    // Keeps playing when the section is collapsed
+    if let Some(start) = morph.start {
+        morph.amount = (start.elapsed().as_secs_f32() / morph.duration).min(1.0);
+        changed = true;
+        if morph.amount >= 1.0 {
+            morph.start = None;
+        }
+    }
+    if changed {
+        metadata.morph(morph.from, morph.to, morph.amount);
+    }
+}
+
+/// Combo box to pick a preset by index
+fn select_preset(ui: &mut Ui, id: &str, presets: &[Preset], index: &mut usize) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_id_source(id)
+        .width(80.0)
+        .selected_text(presets[*index].name.as_str())
+        .show_ui(ui, |ui| {
+            for (i, preset) in presets.iter().enumerate() {
+                changed |= ui.selectable_value(index, i, &preset.name).changed();
+            }
+        });
+    changed
+}
+
+/// Button to bind oscillators to all the components of a number param, or to remove them
+fn lfo_button(ui: &mut Ui, param: &mut Param) {
+    let ranges = match param.slider.lfo_ranges() {
+        Some(ranges) => ranges,
+        None => return,
+    };
+    if ui
+        .selectable_label(!param.lfos.is_empty(), "∿")
+        .on_hover_text("Drive the value with oscillators")
+        .clicked()
+    {
+        param.lfos = if param.lfos.is_empty() {
+            ranges
+                .iter()
+                .map(|(min, max)| Some(Lfo::new(*min, *max)))
+                .collect()
+        } else {
+            Vec::new()
+        };
+    }
+}
+
+/// Oscillators of the components of a param, each one can be toggled on vectors and matrices
+fn edit_lfos(ui: &mut Ui, param: &mut Param) {
+    let name = param.slider.name();
+    let ranges = param.slider.lfo_ranges().unwrap_or_default();
+    let names = component_names(&param.slider);
+    let lfos = &mut param.lfos;
+    let count = lfos.len();
+    ui.vertical(|ui| {
+        for (i, lfo) in lfos.iter_mut().enumerate() {
+            if count == 1 {
+                if let Some(lfo) = lfo.as_mut() {
+                    edit_lfo(ui, (name, i), lfo);
+                }
+                continue;
+            }
+            ui.horizontal(|ui| {
+                if ui
+                    .selectable_label(lfo.is_some(), names.get(i).map_or("", String::as_str))
+                    .clicked()
+                {
+                    *lfo = match lfo {
+                        Some(_) => None,
+                        None => ranges.get(i).map(|(min, max)| Lfo::new(*min, *max)),
+                    };
+                }
+                if let Some(lfo) = lfo.as_mut() {
+                    edit_lfo(ui, (name, i), lfo);
+                }
+            });
         }
+    });
+}
+
+/// x, y, z and w for vectors, [column][row] for matrices, prefixed with the index of the item
+/// for arrays
+fn component_names(slider: &Slider) -> Vec<String> {
+    if let Slider::Array { items, .. } = slider {
+        return items
+            .iter()
+            .enumerate()
+            .flat_map(|(i, item)| {
+                let names = component_names(item);
+                let single = names.len() == 1;
+                names
+                    .into_iter()
+                    .map(move |name| match name.chars().next() {
+                        _ if single => format!("[{}]", i),
+                        Some('[') => format!("[{}]{}", i, name),
+                        _ => format!("[{}].{}", i, name),
+                    })
+            })
+            .collect();
+    }
+    let count = slider.components().map_or(0, |it| it.len());
+    (0..count)
+        .map(|i| {
+            if count <= 4 {
+                ["x", "y", "z", "w"][i].to_string()
+            } else {
+                let size = (count as f32).sqrt() as usize;
+                format!("[{}][{}]", i / size, i % size)
+            }
+        })
+        .collect()
+}
+
+/// Waveform, frequency, phase, amplitude and offset of the oscillator of a param component
+fn edit_lfo(ui: &mut Ui, id: (&str, usize), lfo: &mut Lfo) {
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source(("lfo", id))
+                .width(80.0)
+                .selected_text(lfo.waveform.name())
+                .show_ui(ui, |ui| {
+                    for waveform in Waveform::ALL.iter() {
+                        ui.selectable_value(&mut lfo.waveform, *waveform, waveform.name());
+                    }
+                });
+            ui.add(
+                DragValue::new(&mut lfo.frequency)
+                    .suffix(" Hz")
+                    .clamp_range(0.0..=100.0)
+                    .speed(0.01)
+                    .max_decimals(3),
+            );
+            ui.add(
+                DragValue::new(&mut lfo.phase)
+                    .prefix("phase : ")
+                    .clamp_range(0.0..=1.0)
+                    .speed(0.005)
+                    .max_decimals(3),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                DragValue::new(&mut lfo.amplitude)
+                    .prefix("amp : ")
+                    .speed(0.01)
+                    .max_decimals(3),
+            );
+            ui.add(
+                DragValue::new(&mut lfo.offset)
+                    .prefix("offset : ")
+                    .speed(0.01)
+                    .max_decimals(3),
+            );
+        });
+    });
+}
+
+/// Button to keyframe the param value at the given time, filled when the param is animated
+fn keyframe_button(ui: &mut Ui, param: &mut Param, time: f32) {
+    if param.slider.components().is_none() {
+        return;
+    }
+    let text = if param.keyframes.is_empty() {
+        "◇"
+    } else {
+        "◆"
+    };
+    if ui
+        .small_button(text)
+        .on_hover_text("Keyframe the current value")
+        .clicked()
+    {
+        param.set_keyframe(time);
+    }
+}
+
+/// Time on the timeline for a position on a track
+fn track_time(rect: egui::Rect, pos: egui::Pos2, duration: f32) -> f32 {
+    ((pos.x - rect.left()) / rect.width()).max(0.0).min(1.0) * duration
+}
+
+/// Background of a timeline track with a tick every second and the current time
+fn draw_track(ui: &Ui, rect: egui::Rect, time: f32, duration: f32) {
+    let painter = ui.painter();
+    let stroke = ui.visuals().widgets.inactive.fg_stroke;
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    if duration <= 60.0 {
+        for second in 0..=duration as u32 {
+            let x = rect.left() + second as f32 / duration * rect.width();
+            painter.line_segment(
+                [pos2(x, rect.bottom() - 4.0), pos2(x, rect.bottom())],
+                stroke,
+            );
+        }
+    }
+    let x = rect.left() + (time / duration).min(1.0) * rect.width();
+    painter.line_segment(
+        [pos2(x, rect.top()), pos2(x, rect.bottom())],
+        (1.0, ui.visuals().selection.bg_fill),
+    );
+}
+
+/// Preview bar of a gradient with a row per stop to edit its position and color
+fn edit_gradient(ui: &mut Ui, alpha: bool, stops: &mut Vec<GradientStop>) -> bool {
+    let width = ui.available_width().min(200.0);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, 16.0), Sense::hover());
+    let painter = ui.painter();
+    let steps = 64;
+    for i in 0..steps {
+        let color = sample_gradient(stops, (i as f32 + 0.5) / steps as f32);
+        let left = rect.left() + rect.width() * i as f32 / steps as f32;
+        let right = rect.left() + rect.width() * (i + 1) as f32 / steps as f32;
+        painter.rect_filled(
+            egui::Rect::from_x_y_ranges(left..=right, rect.y_range()),
+            0.0,
+            Rgba::from_rgb(color.x, color.y, color.z),
+        );
+    }
+
+    let mut removed = None;
+    let mut moved = false;
+    let mut changed = false;
+    for (i, stop) in stops.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            moved |= ui
+                .add(
+                    DragValue::new(&mut stop.position)
+                        .clamp_range(0.0..=1.0)
+                        .speed(0.005)
+                        .max_decimals(3),
+                )
+                .changed();
+            let mut color: [f32; 4] = stop.color.into();
+            if alpha {
+                changed |= ui.color_edit_button_rgba_unmultiplied(&mut color).changed();
+            } else {
+                let mut rgb = [color[0], color[1], color[2]];
+                changed |= ui.color_edit_button_rgb(&mut rgb).changed();
+                color[..3].copy_from_slice(&rgb);
+            }
+            stop.color = color.into();
+            if ui.small_button("✖").on_hover_text("Remove stop").clicked() {
+                removed = Some(i);
+            }
+        });
+    }
+    // A gradient needs at least a stop
+    if let Some(i) = removed.filter(|_| stops.len() > 1) {
+        stops.remove(i);
+        changed = true;
+    }
+    if moved {
+        stops.sort_by(|a, b| a.position.partial_cmp(&b.position).unwrap());
+        changed = true;
+    }
+
+    if ui.small_button("+").on_hover_text("Add stop").clicked() {
+        // Split the largest gap between stops
+        let mut bounds: Vec<f32> = stops.iter().map(|it| it.position).collect();
+        bounds.insert(0, 0.0);
+        bounds.push(1.0);
+        let (start, end) = bounds
+            .windows(2)
+            .map(|it| (it[0], it[1]))
+            .fold((0.0, 0.0), |a, b| if b.1 - b.0 > a.1 - a.0 { b } else { a });
+        let position = (start + end) / 2.0;
+        stops.push(GradientStop {
+            position,
+            color: sample_gradient(stops, position),
+        });
+        stops.sort_by(|a, b| a.position.partial_cmp(&b.position).unwrap());
+        changed = true;
+    }
+    changed
+}
+
+/// Plot of a curve with draggable control points.
+/// Double click to add a point, right click on a point to remove it.
+fn edit_curve(ui: &mut Ui, min: f32, max: f32, points: &mut Vec<Vector2<f32>>) -> bool {
+    let width = ui.available_width().min(200.0);
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(width, width * 0.6), Sense::click());
+    let to_screen = |p: Vector2<f32>| {
+        let y = if max > min {
+            (p.y - min) / (max - min)
+        } else {
+            0.0
+        };
+        pos2(
+            rect.left() + p.x * rect.width(),
+            rect.bottom() - y * rect.height(),
+        )
+    };
+    let from_screen = |pos: egui::Pos2| {
+        let x = ((pos.x - rect.left()) / rect.width()).max(0.0).min(1.0);
+        let y = ((rect.bottom() - pos.y) / rect.height()).max(0.0).min(1.0);
+        Vector2::from([x, min + y * (max - min)])
+    };
+
+    let mut removed = None;
+    let mut moved = false;
+    for (i, point) in points.iter_mut().enumerate() {
+        let handle = egui::Rect::from_center_size(to_screen(*point), egui::Vec2::splat(10.0));
+        let response = ui.interact(handle, response.id.with(i), Sense::click_and_drag());
+        if let Some(pos) = response
+            .interact_pointer_pos()
+            .filter(|_| response.dragged())
+        {
+            *point = from_screen(pos);
+            moved = true;
+        }
+        if response.secondary_clicked() {
+            removed = Some(i);
+        }
+    }
+    // A curve needs at least a point
+    if let Some(i) = removed.filter(|_| points.len() > 1) {
+        points.remove(i);
+        moved = true;
+    }
+    if response.double_clicked() {
+        if let Some(pos) = response.interact_pointer_pos() {
+            points.push(from_screen(pos));
+            moved = true;
+        }
+    }
+    if moved {
+        points.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+    }
+
+    let painter = ui.painter();
+    let stroke = ui.visuals().widgets.inactive.fg_stroke;
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    let line = (0..=64)
+        .map(|i| {
+            let x = i as f32 / 64.0;
+            let y = sample_curve(points, x).max(min).min(max);
+            to_screen(Vector2::from([x, y]))
+        })
+        .collect();
+    painter.add(egui::Shape::line(line, stroke));
+    for point in points.iter() {
+        painter.circle_filled(to_screen(*point), 4.0, ui.visuals().selection.bg_fill);
+    }
+    moved
+}
+
+/// Edit the components of a vector on a row, each with its own bounds and speed
+fn drag_vector(ui: &mut Ui, values: &mut [f32], min: &[f32], max: &[f32], speed: &[f32]) -> bool {
+    let mut changed = false;
+    ui.spacing_mut().item_spacing.x = 2.0;
+    ui.columns(values.len(), |columns| {
+        for (i, column) in columns.iter_mut().enumerate() {
+            changed |= column
+                .add(
+                    DragValue::new(&mut values[i])
+                        .clamp_range(min[i]..=max[i])
+                        .speed(speed[i])
+                        .max_decimals(3),
+                )
+                .changed();
+        }
+    });
+    changed
+}
+
+/// Integer drag values, dragging over the widget width goes through the whole range.
+fn drag_int_vector<T>(ui: &mut Ui, values: &mut [T], min: &[T], max: &[T]) -> bool
+where
+    T: Copy + Into<i64> + TryFrom<i64>,
+{
+    let mut changed = false;
+    let width = ui.available_width() / values.len() as f32;
+    ui.spacing_mut().item_spacing.x = 2.0;
+    ui.columns(values.len(), |columns| {
+        for (i, column) in columns.iter_mut().enumerate() {
+            // Computed on i64 and f64 so wide ranges can't overflow
+            let (lo, hi): (i64, i64) = (min[i].into(), max[i].into());
+            let current: i64 = values[i].into();
+            let mut value = current as f64;
+            if column
+                .add(
+                    DragValue::new(&mut value)
+                        .clamp_range(lo as f64..=hi as f64)
+                        .speed((hi - lo) as f64 / width as f64)
+                        .max_decimals(0),
+                )
+                .changed()
+            {
+                if let Ok(value) = T::try_from((value.round() as i64).max(lo).min(hi)) {
+                    values[i] = value;
+                    changed = true;
+                }
+            }
+        }
+    });
+    changed
+}
+
+/// Trackball like widget to rotate a normalized vector. The disc shows the vector seen from the
+/// front, x to the right and y up. The point is hollow when the vector goes away from the viewer.
+fn drag_direction(ui: &mut Ui, value: &mut Vector3<f32>) {
+    let size = 60.0;
+    let (rect, response) = ui.allocate_exact_size(egui::Vec2::splat(size), Sense::drag());
+    if response.dragged() {
+        let delta = response.drag_delta();
+        let (azimuth, elevation) = to_spherical(value);
+        *value = from_spherical(azimuth + delta.x, elevation - delta.y);
+    }
+
+    let radius = size / 2.0;
+    let center = rect.center();
+    let point = pos2(center.x + value.x * radius, center.y - value.y * radius);
+    let painter = ui.painter();
+    let stroke = ui.visuals().widgets.inactive.fg_stroke;
+    let color = ui.visuals().selection.bg_fill;
+    painter.circle_filled(center, radius, ui.visuals().extreme_bg_color);
+    painter.line_segment([center, point], stroke);
+    if value.z >= 0.0 {
+        painter.circle_filled(point, 4.0, color);
+    } else {
+        painter.circle_stroke(point, 4.0, (1.5, color));
+    }
+}
+
+/// Azimuth around y and elevation in degrees of a normalized vector
+fn to_spherical(value: &Vector3<f32>) -> (f32, f32) {
+    (
+        value.x.atan2(value.z).to_degrees(),
+        value.y.max(-1.0).min(1.0).asin().to_degrees(),
+    )
+}
+
+/// Normalized vector from an azimuth around y and an elevation in degrees
+fn from_spherical(azimuth: f32, elevation: f32) -> Vector3<f32> {
+    let (sin_az, cos_az) = azimuth.to_radians().sin_cos();
+    let (sin_el, cos_el) = elevation.max(-90.0).min(90.0).to_radians().sin_cos();
+    Vector3::from([cos_el * sin_az, sin_el, cos_el * cos_az])
+}
+
+/// Edit a matrix as a grid of drag values, laid out like the usual math notation
+/// (a column of the grid is a column of the matrix)
+fn drag_matrix<const N: usize>(ui: &mut Ui, columns: &mut [[f32; N]; N]) -> bool {
+    let mut changed = false;
+    ui.spacing_mut().item_spacing.x = 2.0;
+    for row in 0..N {
+        ui.columns(N, |cells| {
+            for (col, cell) in cells.iter_mut().enumerate() {
+                changed |= cell
+                    .add(
+                        DragValue::new(&mut columns[col][row])
+                            .speed(0.01)
+                            .max_decimals(3),
+                    )
+                    .changed();
+            }
+        });
+    }
+    changed
+}
+
+/// Edit the 2d linear part of a matrix (the first 2 components of its first 2 columns)
+/// as a rotation and a scale on each axis. Shearing is lost when editing.
+fn drag_rotation_scale(ui: &mut Ui, x: &mut [f32], y: &mut [f32]) -> bool {
+    let det = x[0] * y[1] - x[1] * y[0];
+    let mut angle = x[1].atan2(x[0]).to_degrees();
+    let mut scale_x = x[0].hypot(x[1]);
+    // A negative determinant means the matrix flips an axis
+    let mut scale_y = y[0].hypot(y[1]).copysign(det);
+
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(
+                DragValue::new(&mut angle)
+                    .suffix("°")
+                    .clamp_range(-180.0..=180.0)
+                    .max_decimals(1),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                DragValue::new(&mut scale_x)
+                    .prefix("x : ")
+                    .speed(0.01)
+                    .max_decimals(3),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                DragValue::new(&mut scale_y)
+                    .prefix("y : ")
+                    .speed(0.01)
+                    .max_decimals(3),
+            )
+            .changed();
+    });
+
+    if changed {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        x[0] = scale_x * cos;
+        x[1] = scale_x * sin;
+        y[0] = -scale_y * sin;
+        y[1] = scale_y * cos;
     }
+    changed
 }
@@ -0,0 +1,149 @@
+//! Keyboard input made available to shaders.
+
+use winit::event::VirtualKeyCode;
+
+/// Width of the keyboard texture, a texel per key code
+pub const KEYBOARD_SIZE: usize = 256;
+
+/// Keys that can be bound to params, by the name used in the shader source, with their javascript
+/// key code used as the position in the keyboard texture like on Shadertoy
+const KEYS: &[(&str, VirtualKeyCode, u8)] = &[
+    ("A", VirtualKeyCode::A, 65),
+    ("B", VirtualKeyCode::B, 66),
+    ("C", VirtualKeyCode::C, 67),
+    ("D", VirtualKeyCode::D, 68),
+    ("E", VirtualKeyCode::E, 69),
+    ("F", VirtualKeyCode::F, 70),
+    ("G", VirtualKeyCode::G, 71),
+    ("H", VirtualKeyCode::H, 72),
+    ("I", VirtualKeyCode::I, 73),
+    ("J", VirtualKeyCode::J, 74),
+    ("K", VirtualKeyCode::K, 75),
+    ("L", VirtualKeyCode::L, 76),
+    ("M", VirtualKeyCode::M, 77),
+    ("N", VirtualKeyCode::N, 78),
+    ("O", VirtualKeyCode::O, 79),
+    ("P", VirtualKeyCode::P, 80),
+    ("Q", VirtualKeyCode::Q, 81),
+    ("R", VirtualKeyCode::R, 82),
+    ("S", VirtualKeyCode::S, 83),
+    ("T", VirtualKeyCode::T, 84),
+    ("U", VirtualKeyCode::U, 85),
+    ("V", VirtualKeyCode::V, 86),
+    ("W", VirtualKeyCode::W, 87),
+    ("X", VirtualKeyCode::X, 88),
+    ("Y", VirtualKeyCode::Y, 89),
+    ("Z", VirtualKeyCode::Z, 90),
+    ("0", VirtualKeyCode::Key0, 48),
+    ("1", VirtualKeyCode::Key1, 49),
+    ("2", VirtualKeyCode::Key2, 50),
+    ("3", VirtualKeyCode::Key3, 51),
+    ("4", VirtualKeyCode::Key4, 52),
+    ("5", VirtualKeyCode::Key5, 53),
+    ("6", VirtualKeyCode::Key6, 54),
+    ("7", VirtualKeyCode::Key7, 55),
+    ("8", VirtualKeyCode::Key8, 56),
+    ("9", VirtualKeyCode::Key9, 57),
+    ("Space", VirtualKeyCode::Space, 32),
+    ("Enter", VirtualKeyCode::Return, 13),
+    ("Escape", VirtualKeyCode::Escape, 27),
+    ("Tab", VirtualKeyCode::Tab, 9),
+    ("Backspace", VirtualKeyCode::Back, 8),
+    ("Left", VirtualKeyCode::Left, 37),
+    ("Up", VirtualKeyCode::Up, 38),
+    ("Right", VirtualKeyCode::Right, 39),
+    ("Down", VirtualKeyCode::Down, 40),
+    ("Shift", VirtualKeyCode::LShift, 16),
+    ("Control", VirtualKeyCode::LControl, 17),
+    ("Alt", VirtualKeyCode::LAlt, 18),
+    ("PageUp", VirtualKeyCode::PageUp, 33),
+    ("PageDown", VirtualKeyCode::PageDown, 34),
+    ("End", VirtualKeyCode::End, 35),
+    ("Home", VirtualKeyCode::Home, 36),
+    ("Insert", VirtualKeyCode::Insert, 45),
+    ("Delete", VirtualKeyCode::Delete, 46),
+    ("F2", VirtualKeyCode::F2, 113),
+    ("F3", VirtualKeyCode::F3, 114),
+    ("F4", VirtualKeyCode::F4, 115),
+    ("F5", VirtualKeyCode::F5, 116),
+    ("F6", VirtualKeyCode::F6, 117),
+    ("F7", VirtualKeyCode::F7, 118),
+    ("F8", VirtualKeyCode::F8, 119),
+    ("F9", VirtualKeyCode::F9, 120),
+    ("F10", VirtualKeyCode::F10, 121),
+    ("F11", VirtualKeyCode::F11, 122),
+    ("F12", VirtualKeyCode::F12, 123),
+];
+
+/// A key by its name, ignoring case
+pub fn key_from_name(name: &str) -> Option<VirtualKeyCode> {
+    KEYS.iter()
+        .find(|(key, _, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, key, _)| *key)
+}
+
+/// Name of a key that can be bound
+pub fn key_name(key: VirtualKeyCode) -> Option<&'static str> {
+    KEYS.iter()
+        .find(|(_, it, _)| *it == key)
+        .map(|(name, _, _)| *name)
+}
+
+/// Position of a key in the keyboard texture
+pub fn key_code(key: VirtualKeyCode) -> Option<u8> {
+    KEYS.iter()
+        .find(|(_, it, _)| *it == key)
+        .map(|(_, _, code)| *code)
+}
+
+/// Both shift, control and alt keys act as one
+pub fn normalize_key(key: VirtualKeyCode) -> VirtualKeyCode {
+    match key {
+        VirtualKeyCode::RShift => VirtualKeyCode::LShift,
+        VirtualKeyCode::RControl => VirtualKeyCode::LControl,
+        VirtualKeyCode::RAlt => VirtualKeyCode::LAlt,
+        VirtualKeyCode::NumpadEnter => VirtualKeyCode::Return,
+        key => key,
+    }
+}
+
+/// State of the keyboard uploaded to the shader, as 3 rows of [KEYBOARD_SIZE] texels : keys held
+/// down, keys pressed this frame and keys toggled by each press
+pub struct Keyboard {
+    data: Vec<u8>,
+}
+
+impl Default for Keyboard {
+    fn default() -> Self {
+        Self {
+            data: vec![0; KEYBOARD_SIZE * 3],
+        }
+    }
+}
+
+impl Keyboard {
+    pub fn key_event(&mut self, key: VirtualKeyCode, pressed: bool) {
+        if let Some(code) = key_code(key) {
+            let i = code as usize;
+            if pressed {
+                self.data[i] = 255;
+                self.data[KEYBOARD_SIZE + i] = 255;
+                self.data[KEYBOARD_SIZE * 2 + i] ^= 255;
+            } else {
+                self.data[i] = 0;
+            }
+        }
+    }
+
+    /// Presses only last for the frame following them
+    pub fn end_frame(&mut self) {
+        for it in &mut self.data[KEYBOARD_SIZE..KEYBOARD_SIZE * 2] {
+            *it = 0;
+        }
+    }
+
+    /// Texels of the keyboard texture
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
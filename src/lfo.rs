@@ -0,0 +1,90 @@
+//! Oscillators modulating params over time.
+
+use crate::random::Rng;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Waveform {
+    Sine,
+    Triangle,
+    Saw,
+    Square,
+    /// Smooth random values, a new one every period
+    Noise,
+}
+
+impl Waveform {
+    pub const ALL: [Waveform; 5] = [
+        Waveform::Sine,
+        Waveform::Triangle,
+        Waveform::Saw,
+        Waveform::Square,
+        Waveform::Noise,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Waveform::Sine => "sine",
+            Waveform::Triangle => "triangle",
+            Waveform::Saw => "saw",
+            Waveform::Square => "square",
+            Waveform::Noise => "noise",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|it| it.name() == name)
+    }
+
+    /// Value between -1 and 1 at the given number of periods
+    fn sample(&self, x: f32) -> f32 {
+        let t = x.rem_euclid(1.0);
+        match self {
+            Waveform::Sine => (t * std::f32::consts::TAU).sin(),
+            Waveform::Triangle => 1.0 - 4.0 * (t - 0.5).abs(),
+            Waveform::Saw => 2.0 * t - 1.0,
+            Waveform::Square => {
+                if t < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Noise => {
+                let i = x.floor() as i64 as u64;
+                let a = Rng::new(i).next_signed();
+                let b = Rng::new(i.wrapping_add(1)).next_signed();
+                a + (b - a) * t * t * (3.0 - 2.0 * t)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Lfo {
+    pub waveform: Waveform,
+    /// In Hz
+    pub frequency: f32,
+    /// Fraction of a period, between 0 and 1
+    pub phase: f32,
+    pub amplitude: f32,
+    /// Center of the oscillation
+    pub offset: f32,
+}
+
+impl Lfo {
+    /// An oscillator going through the whole range
+    pub fn new(min: f32, max: f32) -> Self {
+        Self {
+            waveform: Waveform::Sine,
+            frequency: 0.5,
+            phase: 0.0,
+            amplitude: (max - min) / 2.0,
+            offset: (max + min) / 2.0,
+        }
+    }
+
+    /// Value at the given time in sec
+    pub fn sample(&self, time: f32) -> f32 {
+        self.offset + self.amplitude * self.waveform.sample(time * self.frequency + self.phase)
+    }
+}
@@ -1,33 +1,44 @@
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::mpsc::Receiver;
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
+use chrono::{Datelike, Local, Timelike};
 use crevice::std430::{AsStd430, Std430};
 use egui::{FontDefinitions, Style};
 use egui_wgpu_backend::ScreenDescriptor;
 use egui_winit_platform::{Platform, PlatformDescriptor};
 use image::{ImageBuffer, ImageFormat, Rgba};
 use log::{debug, error, info};
-use mint::Vector2;
+use mint::{Vector2, Vector4};
 use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
 use rfd::FileDialog;
 use wgpu::PowerPreference;
-use winit::event::{Event, MouseScrollDelta, VirtualKeyCode, WindowEvent};
+use winit::event::{
+    ElementState, Event, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
+};
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::Window;
 
 use crate::gui::Gui;
-use crate::renderer::Renderer;
+use crate::input::Keyboard;
+use crate::renderer::{FrameUploads, Renderer};
 use crate::shader::Shader;
 use crate::shader_loader::ShaderLoader;
+use crate::timeline::Timeline;
 
 mod gui;
+pub mod input;
+pub mod lfo;
 pub mod preprocessor;
+pub mod presets;
+pub mod random;
 pub mod renderer;
 pub mod shader;
 pub mod shader_loader;
+pub mod timeline;
 
 #[derive(Debug)]
 pub enum Command {
@@ -43,6 +54,8 @@ pub enum Command {
     ResetGlobals,
     /// Reset the shader params to their default
     ResetParams,
+    /// Write the presets of the shader to disk
+    SavePresets,
     /// Export a render of the current shader
     ExportImage,
     Pause,
@@ -51,6 +64,9 @@ pub enum Command {
     Exit,
 }
 
+/// Touchpad scrolling in px counted as a mouse wheel line
+const PIXELS_PER_LINE: f64 = 20.0;
+
 /// The globals we pass to the fragment shader
 #[derive(AsStd430, Clone)]
 pub struct Globals {
@@ -66,19 +82,100 @@ pub struct Globals {
     pub time: f32,
     /// Number of frame
     pub frame: u32,
+    /// Like Shadertoy iMouse : xy is the mouse pos while the left button is down, zw the pos of
+    /// the last click, z is negative when the button is up and w after the click frame
+    pub mouse_click: Vector4<f32>,
+    /// Mouse pos divided by the resolution
+    pub mouse_norm: Vector2<f32>,
+    /// Mouse buttons down, left is 1, right 2 and middle 4
+    pub mouse_buttons: u32,
+    /// Time since the last frame in sec
+    pub time_delta: f32,
+    /// Smoothed number of frames per sec
+    pub frame_rate: f32,
+    /// Local date : year, month, day and seconds since midnight
+    pub date: Vector4<f32>,
 }
 
 impl Globals {
     pub fn reset(&mut self) {
         self.frame = 0;
         self.time = 0.0;
+        self.time_delta = 0.0;
+        self.frame_rate = 0.0;
         self.mouse_wheel = 0.0;
     }
+
+    /// Time of the frame about to be rendered, in sec
+    fn set_time(&mut self, time: f32) {
+        // Seeking backward is not a negative delta
+        self.time_delta = (time - self.time).max(0.0);
+        self.time = time;
+        if self.time_delta > 0.0 {
+            let rate = 1.0 / self.time_delta;
+            self.frame_rate = if self.frame_rate > 0.0 {
+                self.frame_rate * 0.9 + rate * 0.1
+            } else {
+                rate
+            };
+        }
+
+        let now = Local::now();
+        self.date = Vector4::from([
+            now.year() as f32,
+            now.month() as f32,
+            now.day() as f32,
+            now.num_seconds_from_midnight() as f32 + now.nanosecond() as f32 / 1e9,
+        ]);
+    }
+
+    /// Time is frozen while paused
+    fn pause(&mut self) {
+        self.time_delta = 0.0;
+        self.frame_rate = 0.0;
+    }
+
+    /// Mouse pos on the canvas, in px
+    fn set_mouse(&mut self, x: u32, y: u32) {
+        self.mouse = Vector2::from([x, y]);
+        self.mouse_norm = Vector2::from([
+            x as f32 / self.resolution.x as f32,
+            y as f32 / self.resolution.y as f32,
+        ]);
+        if self.mouse_buttons & 1 != 0 {
+            self.mouse_click.x = x as f32;
+            self.mouse_click.y = y as f32;
+        }
+    }
+
+    fn mouse_button(&mut self, button: u32, pressed: bool) {
+        if pressed {
+            self.mouse_buttons |= button;
+        } else {
+            self.mouse_buttons &= !button;
+        }
+        if button == 1 {
+            let (x, y) = (self.mouse.x as f32, self.mouse.y as f32);
+            if pressed {
+                self.mouse_click = Vector4::from([x, y, x, y]);
+            } else {
+                self.mouse_click.z = -self.mouse_click.z.abs();
+            }
+        }
+    }
+
+    /// The click is only signaled on the frame following it
+    fn end_frame(&mut self) {
+        self.frame += 1;
+        self.mouse_click.w = -self.mouse_click.w.abs();
+    }
 }
 
 pub struct Settings {
     pub target_framerate: Duration,
     pub mouse_wheel_step: f32,
+    /// Keep the param values when reloading the same shader
+    pub preserve_params: bool,
 }
 
 pub struct ExportData {
@@ -124,6 +221,12 @@ pub struct Nuance {
     /// Reset on simulation restart
     sim_duration: Duration,
     paused: bool,
+    /// Keyframe animation settings
+    timeline: Timeline,
+    /// Keys currently held down
+    keys_down: HashSet<VirtualKeyCode>,
+    /// Keys state passed to shaders
+    keyboard: Keyboard,
 
     /// Export configuration
     export_data: ExportData,
@@ -169,6 +272,7 @@ impl Nuance {
             settings: Settings {
                 target_framerate: Duration::from_secs_f32(1.0 / 60.0),
                 mouse_wheel_step: 0.1,
+                preserve_params: true,
             },
             shader: None,
             shader_loader: ShaderLoader::new(),
@@ -183,10 +287,19 @@ impl Nuance {
                 ratio: (canvas_size.width) as f32 / canvas_size.height as f32,
                 time: 0.0,
                 frame: 0,
+                mouse_click: Vector4::from([0.0, 0.0, 0.0, 0.0]),
+                mouse_norm: Vector2::from([0.0, 0.0]),
+                mouse_buttons: 0,
+                time_delta: 0.0,
+                frame_rate: 0.0,
+                date: Vector4::from([0.0, 0.0, 0.0, 0.0]),
             },
             sim_start: Instant::now(),
             sim_duration: Duration::from_nanos(0),
             paused: false,
+            timeline: Default::default(),
+            keys_down: HashSet::new(),
+            keyboard: Default::default(),
             export_data: Default::default(),
         })
     }
@@ -216,7 +329,7 @@ impl Nuance {
                     Command::Load => {
                         if let Some(path) = FileDialog::new()
                             .set_parent(&self.window)
-                            .add_filter("Shaders", &["glsl", "frag", "spv"])
+                            .add_filter("Shaders", &["glsl", "frag", "wgsl", "spv"])
                             .pick_file()
                         {
                             self.unwatch();
@@ -248,6 +361,9 @@ impl Nuance {
                             metadata.reset_params();
                         }
                     }
+                    Command::SavePresets => {
+                        self.save_presets();
+                    }
                     Command::ExportImage => {
                         if let Some(path) = FileDialog::new()
                             .set_parent(&self.window)
@@ -276,10 +392,23 @@ impl Nuance {
                     } => {
                         let scale_factor = self.window.scale_factor();
                         if position.x > self.gui.ui_width as f64 * scale_factor {
-                            self.globals.mouse = Vector2::from([
+                            self.globals.set_mouse(
                                 (position.x - self.gui.ui_width as f64 * scale_factor) as u32,
                                 position.y as u32,
-                            ]);
+                            );
+                        }
+                    }
+                    WindowEvent::MouseInput { state, button, .. } => {
+                        let button = match button {
+                            MouseButton::Left => 1,
+                            MouseButton::Right => 2,
+                            MouseButton::Middle => 4,
+                            MouseButton::Other(_) => 0,
+                        };
+                        let pressed = state == ElementState::Pressed;
+                        // Presses go to the shader unless they are on the ui
+                        if button != 0 && (!pressed || self.gui.canvas_hovered) {
+                            self.globals.mouse_button(button, pressed);
                         }
                     }
                     WindowEvent::MouseWheel {
@@ -290,16 +419,31 @@ impl Nuance {
                         MouseScrollDelta::LineDelta(_, value) => {
                             self.globals.mouse_wheel += value * self.settings.mouse_wheel_step;
                         }
+                        // Touchpads scroll by pixels
                         MouseScrollDelta::PixelDelta(pos) => {
-                            info!("{:?}", pos);
+                            self.globals.mouse_wheel +=
+                                (pos.y / PIXELS_PER_LINE) as f32 * self.settings.mouse_wheel_step;
                         }
                     },
                     WindowEvent::KeyboardInput { input, .. } => match input.virtual_keycode {
                         Some(VirtualKeyCode::F1) => {
                             self.gui.profiling_window = true;
                         }
+                        // Presses go to the shader unless a text field has focus
+                        Some(key) => {
+                            let pressed = input.state == ElementState::Pressed;
+                            if !pressed || !self.gui.context().wants_keyboard_input() {
+                                self.key_event(crate::input::normalize_key(key), pressed);
+                            }
+                        }
                         _ => {}
                     },
+                    // Releases are missed while the window is unfocused
+                    WindowEvent::Focused(false) => {
+                        for key in self.keys_down.clone() {
+                            self.key_event(key, false);
+                        }
+                    }
                     WindowEvent::CloseRequested => {
                         *control_flow = ControlFlow::Exit;
                     }
@@ -316,12 +460,6 @@ impl Nuance {
                             Instant::now() + self.settings.target_framerate - since_last_draw,
                         );
                     }
-
-                    // Update shader timem
-                    if !self.is_paused() {
-                        self.globals.time =
-                            (self.sim_start.elapsed() + self.sim_duration).as_secs_f32();
-                    }
                 }
                 Event::RedrawRequested(_) => {
                     // Tell the profiler we're running a new frame
@@ -330,6 +468,24 @@ impl Nuance {
                     // Update egui frame time from app start time
                     self.gui.update_time(start_time.elapsed().as_secs_f64());
 
+                    // Update shader time, once per frame for a consistent delta
+                    if !self.is_paused() {
+                        self.globals
+                            .set_time((self.sim_start.elapsed() + self.sim_duration).as_secs_f32());
+                    }
+
+                    // Animated params are evaluated before being displayed and uploaded,
+                    // they can be edited freely while paused
+                    if let Some(Some(metadata)) =
+                        self.shader.as_mut().map(|it| it.metadata.as_mut())
+                    {
+                        if !self.paused {
+                            metadata.animate(self.timeline.local_time(self.globals.time));
+                        }
+                        // Oscillators only depend on the time, they stay still while paused
+                        metadata.modulate(self.globals.time);
+                    }
+
                     // Query window properties
                     let window_size = self.window.inner_size();
                     let screen_desc = ScreenDescriptor {
@@ -341,24 +497,42 @@ impl Nuance {
                     // Generate the GUI
                     let paint_jobs = Gui::render(&proxy, &screen_desc, &mut self);
 
+                    // Lookup textures are only baked after an edit, and kept for the next
+                    // rendered frame while paused
+                    let lut_buffer = if self.is_paused() {
+                        None
+                    } else {
+                        self.shader
+                            .as_mut()
+                            .and_then(|it| it.metadata.as_mut())
+                            .and_then(|it| it.changed_lut_buffer())
+                    };
+
+                    let params_buffer = self
+                        .shader
+                        .as_ref()
+                        .map(|it| it.metadata.as_ref().map(|it| it.params_buffer()))
+                        .unwrap_or_default()
+                        .unwrap_or_default();
+
                     // Render the UI
                     self.renderer
                         .render(
                             &screen_desc,
                             (&self.gui.texture(), &paint_jobs),
-                            &self
-                                .shader
-                                .as_ref()
-                                .map(|it| it.metadata.as_ref().map(|it| it.params_buffer()))
-                                .unwrap_or_default()
-                                .unwrap_or_default(),
-                            self.globals.as_std430().as_bytes(),
+                            &FrameUploads {
+                                params: &params_buffer,
+                                luts: lut_buffer.as_deref(),
+                                keyboard: self.keyboard.data(),
+                                push_constants: self.globals.as_std430().as_bytes(),
+                            },
                             !self.is_paused(),
                         )
                         .unwrap();
 
                     if !self.is_paused() {
-                        self.globals.frame += 1;
+                        self.globals.end_frame();
+                        self.keyboard.end_frame();
                         last_draw = Instant::now();
                     }
                 }
@@ -373,15 +547,41 @@ impl Nuance {
         let reload_start = Instant::now();
 
         match self.shader_loader.load_shader(&path) {
-            Ok((shader, source)) => {
-                let buffer_size = if let Some(metadata) = shader.metadata.as_ref() {
-                    metadata.params_buffer_size()
+            Ok((mut shader, source)) => {
+                if let Some(metadata) = shader.metadata.as_mut() {
+                    match presets::load(&shader.main) {
+                        Ok(presets) => metadata.presets = presets,
+                        Err(e) => {
+                            error!("Can't load presets : {}", e);
+                            metadata.presets_error = Some(e.to_string());
+                        }
+                    }
+                }
+
+                // Keep the tweaked values when the same shader is reloaded
+                if self.settings.preserve_params {
+                    let old = self
+                        .shader
+                        .as_ref()
+                        .filter(|it| it.main == shader.main)
+                        .and_then(|it| it.metadata.as_ref());
+                    if let (Some(metadata), Some(old)) = (shader.metadata.as_mut(), old) {
+                        metadata.restore_params(old);
+                    }
+                }
+
+                let (buffer_size, lut_count) = if let Some(metadata) = shader.metadata.as_ref() {
+                    (metadata.params_buffer_size(), metadata.lut_count())
                 } else {
-                    0
+                    (0, 0)
                 };
 
-                self.renderer
-                    .set_shader(source, Globals::std430_size_static() as u32, buffer_size);
+                self.renderer.set_shader(
+                    source,
+                    Globals::std430_size_static() as u32,
+                    buffer_size,
+                    lut_count,
+                );
 
                 self.shader = Some(shader);
                 // Reset the running globals
@@ -434,16 +634,28 @@ impl Nuance {
         globals.resolution = *size;
         globals.ratio = globals.resolution.x as f32 / globals.resolution.y as f32;
 
+        let params_buffer = self
+            .shader
+            .as_ref()
+            .map(|it| it.metadata.as_ref().map(|it| it.params_buffer()))
+            .unwrap_or_default()
+            .unwrap_or_default();
+        let lut_buffer = self
+            .shader
+            .as_ref()
+            .map(|it| it.metadata.as_ref().map(|it| it.lut_buffer()))
+            .unwrap_or_default()
+            .unwrap_or_default();
+
         self.renderer
             .render_to_buffer(
                 *size,
-                &self
-                    .shader
-                    .as_ref()
-                    .map(|it| it.metadata.as_ref().map(|it| it.params_buffer()))
-                    .unwrap_or_default()
-                    .unwrap_or_default(),
-                globals.as_std430().as_bytes(),
+                &FrameUploads {
+                    params: &params_buffer,
+                    luts: Some(&lut_buffer[..]),
+                    keyboard: self.keyboard.data(),
+                    push_constants: globals.as_std430().as_bytes(),
+                },
                 |buf| {
                     let image = ImageBuffer::<Rgba<_>, _>::from_raw(size.x, size.y, &buf[..])
                         .context("Can't create image from buffer")?;
@@ -460,9 +672,44 @@ impl Nuance {
         );
     }
 
+    /// Update the keyboard state and the params bound to a key, repeated presses while a key is held are ignored
+    fn key_event(&mut self, key: VirtualKeyCode, pressed: bool) {
+        let changed = if pressed {
+            self.keys_down.insert(key)
+        } else {
+            self.keys_down.remove(&key)
+        };
+        if !changed {
+            return;
+        }
+        self.keyboard.key_event(key, pressed);
+        if let Some(Some(metadata)) = self.shader.as_mut().map(|it| it.metadata.as_mut()) {
+            metadata.key_event(key, pressed);
+        }
+    }
+
+    fn save_presets(&self) {
+        if let Some(shader) = self.shader.as_ref() {
+            if let Some(metadata) = shader.metadata.as_ref() {
+                if let Some(e) = metadata.presets_error.as_ref() {
+                    error!(
+                        "Presets not saved, the presets file couldn't be loaded : {}",
+                        e
+                    );
+                    return;
+                }
+                match presets::save(&shader.main, &metadata.presets) {
+                    Ok(()) => info!("Saved {} presets", metadata.presets.len()),
+                    Err(e) => error!("Can't save presets : {}", e),
+                }
+            }
+        }
+    }
+
     fn pause(&mut self) {
         self.sim_duration += self.sim_start.elapsed();
         self.paused = true;
+        self.globals.pause();
     }
 
     fn is_paused(&self) -> bool {
@@ -474,6 +721,16 @@ impl Nuance {
         self.paused = false;
     }
 
+    /// Jump to a time on the timeline, animated params are updated even when paused
+    fn seek(&mut self, time: f32) {
+        self.sim_start = Instant::now();
+        self.sim_duration = Duration::from_secs_f32(time.max(0.0));
+        self.globals.time = time.max(0.0);
+        if let Some(Some(metadata)) = self.shader.as_mut().map(|it| it.metadata.as_mut()) {
+            metadata.animate(time);
+        }
+    }
+
     fn shader_loaded(&self) -> bool {
         self.shader.is_some()
     }
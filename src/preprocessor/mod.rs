@@ -0,0 +1,2237 @@
+//! Extract information from glsl source and transpiles it to valid glsl source code.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use glsl_lang::ast::{
+    ArraySpecifier, ArraySpecifierDimension, BinaryOp, Declaration, ExternalDeclaration,
+    FunIdentifier, PreprocessorDefine, TypeQualifier, TypeSpecifier, TypeSpecifierNonArray,
+    UnaryOp,
+};
+use glsl_lang::{
+    ast::{
+        Block, Expr, Identifier, IdentifierData, LayoutQualifier, LayoutQualifierSpec, SmolStr,
+        StructFieldSpecifier, TranslationUnit, TypeQualifierSpec,
+    },
+    parse::{Parsable, ParseOptions},
+    transpiler::glsl::FormattingState,
+    visitor::{HostMut, Visit, VisitorMut},
+};
+use log::debug;
+use mint::{ColumnMatrix2, ColumnMatrix3, ColumnMatrix4, Vector2, Vector3, Vector4};
+
+use crate::input;
+use crate::shader::{
+    CanvasCoords, GradientStop, KeyBinding, Param, ShaderMetadata, Slider, LUT_SIZE,
+};
+
+pub mod wgsl;
+
+/// Prefix of the identifiers replacing string literals in layout qualifiers
+const STRING_PLACEHOLDER: &str = "__nuance_str_";
+/// Max nesting of expressions when folding constants, protects against recursive macros
+const MAX_EVAL_DEPTH: usize = 64;
+/// Qualifiers accepted on any param, they don't change how the value is edited
+const COMMON_QUALIFIERS: &[&str] = &["group", "label"];
+/// Qualifiers turning a field into a lookup texture sampled through a generated function
+const LUT_QUALIFIERS: &[&str] = &["gradient", "curve"];
+
+/// An error in the shader source, found while extracting params
+#[derive(Debug, Clone)]
+pub struct PreprocessError {
+    /// The shader file name
+    pub file: String,
+    /// Line of the error, starting at 1. 0 if unknown.
+    pub line: usize,
+    /// Column of the error, starting at 1. 0 if unknown.
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for PreprocessError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}: {}", self.file, self.message)
+        } else {
+            write!(
+                f,
+                "{}:{}:{}: {}",
+                self.file, self.line, self.column, self.message
+            )
+        }
+    }
+}
+
+impl Error for PreprocessError {}
+
+/// Traverses the ast and extract useful data while converting the ast to valid glsl source
+#[derive(Default)]
+struct Extractor {
+    metadata: ShaderMetadata,
+    /// Name of the file being processed, for error reporting
+    file: String,
+    /// Source being processed, to find line and column of errors
+    source: String,
+    /// String literals found in layout qualifiers, see [extract_strings]
+    strings: Vec<String>,
+    /// Object-like macros defined so far, usable in qualifiers
+    defines: HashMap<String, Expr>,
+    /// Errors encountered while visiting the ast
+    errors: Vec<PreprocessError>,
+}
+
+impl VisitorMut for Extractor {
+    fn visit_block(&mut self, block: &mut Block) -> Visit {
+        if is_params_block(block) {
+            // We got the block we searched for
+            // Gradients and curves are not in the uniform buffer, they are found before their
+            // qualifiers are removed
+            let luts: Vec<bool> = block.fields.iter().map(is_lut_field).collect();
+            for field in block.fields.iter_mut() {
+                match self.add_param_from_field(field) {
+                    Ok(()) => {
+                        convert_field(field);
+                    }
+                    Err(e) => {
+                        self.errors.push(e);
+                    }
+                }
+            }
+            let mut luts = luts.into_iter();
+            block.fields.retain(|_| !luts.next().unwrap_or(false));
+            convert_params_block(block);
+        }
+        Visit::Parent
+    }
+
+    fn visit_preprocessor_define(&mut self, define: &mut PreprocessorDefine) -> Visit {
+        if let PreprocessorDefine::ObjectLike { ident, value } = define {
+            if ident.content.0.as_str() == "NUANCE_STILL_IMAGE" {
+                self.metadata.still_image = true;
+            }
+            // Remember macros so they can be used in qualifiers
+            if let Ok(expr) = Expr::parse(value.as_str()) {
+                self.defines.insert(ident.content.0.to_string(), expr);
+            }
+        }
+        Visit::Parent
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) -> Visit {
+        if let Expr::Dot(expr2, ident1) = expr {
+            if let Expr::Variable(ident0) = expr2.as_ref() {
+                let slider_name = ident0.content.0.as_str();
+                let mut error = None;
+                for slider in self.metadata.sliders() {
+                    match slider {
+                        Slider::Float {
+                            name,
+                            min,
+                            max,
+                            step,
+                            default,
+                            ..
+                        } => {
+                            if name == slider_name {
+                                match ident1.content.0.as_str() {
+                                    "max" => *expr = Expr::FloatConst(*max),
+                                    "min" => *expr = Expr::FloatConst(*min),
+                                    "init" => *expr = Expr::FloatConst(*default),
+                                    "step" => *expr = Expr::FloatConst(*step),
+                                    // No . accessors on a float value
+                                    other => {
+                                        error = Some(format!(
+                                            "No such property '{}' on float param, expected {}",
+                                            other, SCALAR_PROPERTIES
+                                        ))
+                                    }
+                                }
+                                break;
+                            }
+                        }
+                        Slider::Int {
+                            name,
+                            min,
+                            max,
+                            step,
+                            default,
+                            ..
+                        } => {
+                            if name == slider_name {
+                                match ident1.content.0.as_str() {
+                                    "max" => *expr = Expr::IntConst(*max),
+                                    "min" => *expr = Expr::IntConst(*min),
+                                    "init" => *expr = Expr::IntConst(*default),
+                                    "step" => *expr = Expr::IntConst(*step),
+                                    other => {
+                                        error = Some(format!(
+                                            "No such property '{}' on int param, expected {}",
+                                            other, SCALAR_PROPERTIES
+                                        ))
+                                    }
+                                }
+                                break;
+                            }
+                        }
+                        Slider::UInt {
+                            name,
+                            min,
+                            max,
+                            step,
+                            default,
+                            ..
+                        } => {
+                            if name == slider_name {
+                                match ident1.content.0.as_str() {
+                                    "max" => *expr = Expr::UIntConst(*max),
+                                    "min" => *expr = Expr::UIntConst(*min),
+                                    "init" => *expr = Expr::UIntConst(*default),
+                                    "step" => *expr = Expr::UIntConst(*step),
+                                    other => {
+                                        error = Some(format!(
+                                            "No such property '{}' on uint param, expected {}",
+                                            other, SCALAR_PROPERTIES
+                                        ))
+                                    }
+                                }
+                                break;
+                            }
+                        }
+                        Slider::Vec2 {
+                            name,
+                            min,
+                            max,
+                            default,
+                            ..
+                        } => {
+                            if name == slider_name {
+                                match vector_property(
+                                    ident1.content.0.as_str(),
+                                    TypeSpecifierNonArray::Vec2,
+                                    default.as_ref(),
+                                    min.as_ref(),
+                                    max.as_ref(),
+                                ) {
+                                    Ok(Some(value)) => *expr = value,
+                                    // . accessors exists but we won't check them here
+                                    Ok(None) => debug!(
+                                        "No such property '{}' on vec2 param",
+                                        ident1.content.0
+                                    ),
+                                    Err(e) => error = Some(e),
+                                }
+                                break;
+                            }
+                        }
+                        Slider::Pad {
+                            name,
+                            min,
+                            max,
+                            default,
+                            ..
+                        } => {
+                            if name == slider_name {
+                                match vector_property(
+                                    ident1.content.0.as_str(),
+                                    TypeSpecifierNonArray::Vec2,
+                                    default.as_ref(),
+                                    min.as_ref(),
+                                    max.as_ref(),
+                                ) {
+                                    Ok(Some(value)) => *expr = value,
+                                    Ok(None) => debug!(
+                                        "No such property '{}' on vec2 param",
+                                        ident1.content.0
+                                    ),
+                                    Err(e) => error = Some(e),
+                                }
+                                break;
+                            }
+                        }
+                        Slider::Vec3 {
+                            name,
+                            min,
+                            max,
+                            default,
+                            ..
+                        } => {
+                            if name == slider_name {
+                                match vector_property(
+                                    ident1.content.0.as_str(),
+                                    TypeSpecifierNonArray::Vec3,
+                                    default.as_ref(),
+                                    min.as_ref(),
+                                    max.as_ref(),
+                                ) {
+                                    Ok(Some(value)) => *expr = value,
+                                    // . accessors exists but we won't check them here
+                                    Ok(None) => debug!(
+                                        "No such property '{}' on vec3 param",
+                                        ident1.content.0
+                                    ),
+                                    Err(e) => error = Some(e),
+                                }
+                                break;
+                            }
+                        }
+                        Slider::Vec4 {
+                            name,
+                            min,
+                            max,
+                            default,
+                            ..
+                        } => {
+                            if name == slider_name {
+                                match vector_property(
+                                    ident1.content.0.as_str(),
+                                    TypeSpecifierNonArray::Vec4,
+                                    default.as_ref(),
+                                    min.as_ref(),
+                                    max.as_ref(),
+                                ) {
+                                    Ok(Some(value)) => *expr = value,
+                                    // . accessors exists but we won't check them here
+                                    Ok(None) => debug!(
+                                        "No such property '{}' on vec4 param",
+                                        ident1.content.0
+                                    ),
+                                    Err(e) => error = Some(e),
+                                }
+                                break;
+                            }
+                        }
+                        Slider::IVec2 {
+                            name,
+                            min,
+                            max,
+                            step,
+                            default,
+                            ..
+                        } => {
+                            if name == slider_name {
+                                match integer_vector_property(
+                                    ident1.content.0.as_str(),
+                                    TypeSpecifierNonArray::IVec2,
+                                    (default.as_ref(), min.as_ref(), max.as_ref(), *step),
+                                    Expr::IntConst,
+                                ) {
+                                    Some(value) => *expr = value,
+                                    // . accessors exists but we won't check them here
+                                    None => debug!(
+                                        "No such property '{}' on ivec2 param",
+                                        ident1.content.0
+                                    ),
+                                }
+                                break;
+                            }
+                        }
+                        Slider::IVec3 {
+                            name,
+                            min,
+                            max,
+                            step,
+                            default,
+                            ..
+                        } => {
+                            if name == slider_name {
+                                match integer_vector_property(
+                                    ident1.content.0.as_str(),
+                                    TypeSpecifierNonArray::IVec3,
+                                    (default.as_ref(), min.as_ref(), max.as_ref(), *step),
+                                    Expr::IntConst,
+                                ) {
+                                    Some(value) => *expr = value,
+                                    // . accessors exists but we won't check them here
+                                    None => debug!(
+                                        "No such property '{}' on ivec3 param",
+                                        ident1.content.0
+                                    ),
+                                }
+                                break;
+                            }
+                        }
+                        Slider::UVec2 {
+                            name,
+                            min,
+                            max,
+                            step,
+                            default,
+                            ..
+                        } => {
+                            if name == slider_name {
+                                match integer_vector_property(
+                                    ident1.content.0.as_str(),
+                                    TypeSpecifierNonArray::UVec2,
+                                    (default.as_ref(), min.as_ref(), max.as_ref(), *step),
+                                    Expr::UIntConst,
+                                ) {
+                                    Some(value) => *expr = value,
+                                    // . accessors exists but we won't check them here
+                                    None => debug!(
+                                        "No such property '{}' on uvec2 param",
+                                        ident1.content.0
+                                    ),
+                                }
+                                break;
+                            }
+                        }
+                        Slider::UVec3 {
+                            name,
+                            min,
+                            max,
+                            step,
+                            default,
+                            ..
+                        } => {
+                            if name == slider_name {
+                                match integer_vector_property(
+                                    ident1.content.0.as_str(),
+                                    TypeSpecifierNonArray::UVec3,
+                                    (default.as_ref(), min.as_ref(), max.as_ref(), *step),
+                                    Expr::UIntConst,
+                                ) {
+                                    Some(value) => *expr = value,
+                                    // . accessors exists but we won't check them here
+                                    None => debug!(
+                                        "No such property '{}' on uvec3 param",
+                                        ident1.content.0
+                                    ),
+                                }
+                                break;
+                            }
+                        }
+                        Slider::Color { name, default, .. } => {
+                            if name == slider_name {
+                                match ident1.content.0.as_str() {
+                                    "init" => {
+                                        *expr = constructor_expr(
+                                            TypeSpecifierNonArray::Vec3,
+                                            default.as_ref().iter(),
+                                        )
+                                    }
+                                    other => debug!("No such property '{}' on color param", other),
+                                }
+                                break;
+                            }
+                        }
+                        Slider::Direction { name, default, .. } => {
+                            if name == slider_name {
+                                match ident1.content.0.as_str() {
+                                    "init" => {
+                                        *expr = constructor_expr(
+                                            TypeSpecifierNonArray::Vec3,
+                                            default.as_ref().iter(),
+                                        )
+                                    }
+                                    other => {
+                                        debug!("No such property '{}' on direction param", other)
+                                    }
+                                }
+                                break;
+                            }
+                        }
+                        Slider::Euler { name, default, .. } => {
+                            if name == slider_name {
+                                match ident1.content.0.as_str() {
+                                    "init" => {
+                                        *expr = constructor_expr(
+                                            TypeSpecifierNonArray::Vec3,
+                                            default.as_ref().iter(),
+                                        )
+                                    }
+                                    other => debug!("No such property '{}' on euler param", other),
+                                }
+                                break;
+                            }
+                        }
+                        Slider::ColorAlpha { name, default, .. } => {
+                            if name == slider_name {
+                                match ident1.content.0.as_str() {
+                                    "init" => {
+                                        *expr = constructor_expr(
+                                            TypeSpecifierNonArray::Vec4,
+                                            default.as_ref().iter(),
+                                        )
+                                    }
+                                    other => debug!("No such property '{}' on color param", other),
+                                }
+                                break;
+                            }
+                        }
+                        Slider::Mat2 { name, default, .. } => {
+                            if name == slider_name {
+                                let columns: [[f32; 2]; 2] = (*default).into();
+                                match ident1.content.0.as_str() {
+                                    "init" => {
+                                        *expr = constructor_expr(
+                                            TypeSpecifierNonArray::Mat2,
+                                            columns.iter().flatten(),
+                                        )
+                                    }
+                                    other => debug!("No such property '{}' on mat2 param", other),
+                                }
+                                break;
+                            }
+                        }
+                        Slider::Mat3 { name, default, .. } => {
+                            if name == slider_name {
+                                let columns: [[f32; 3]; 3] = (*default).into();
+                                match ident1.content.0.as_str() {
+                                    "init" => {
+                                        *expr = constructor_expr(
+                                            TypeSpecifierNonArray::Mat3,
+                                            columns.iter().flatten(),
+                                        )
+                                    }
+                                    other => debug!("No such property '{}' on mat3 param", other),
+                                }
+                                break;
+                            }
+                        }
+                        Slider::Mat4 { name, default, .. } => {
+                            if name == slider_name {
+                                let columns: [[f32; 4]; 4] = (*default).into();
+                                match ident1.content.0.as_str() {
+                                    "init" => {
+                                        *expr = constructor_expr(
+                                            TypeSpecifierNonArray::Mat4,
+                                            columns.iter().flatten(),
+                                        )
+                                    }
+                                    other => debug!("No such property '{}' on mat4 param", other),
+                                }
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                if let Some(message) = error {
+                    let error = self.error_at(ident1, message);
+                    self.errors.push(error);
+                }
+                return Visit::Parent;
+            }
+        }
+        Visit::Children
+    }
+}
+
+impl Extractor {
+    /// Create an error located at the given identifier
+    fn error_at(&self, ident: &Identifier, message: impl Into<String>) -> PreprocessError {
+        let (line, column) = ident
+            .span
+            .as_ref()
+            .map(|span| position(&self.source, usize::from(span.range().start())))
+            .unwrap_or((0, 0));
+        PreprocessError {
+            file: self.file.clone(),
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Get the value of a qualifier, or fail if it has none
+    fn qualifier_value<'a>(
+        &self,
+        id: &Identifier,
+        param: Option<&'a Expr>,
+    ) -> Result<&'a Expr, PreprocessError> {
+        param.ok_or_else(|| self.error_at(id, format!("Setting '{}' needs a value", id.content.0)))
+    }
+
+    fn invalid_value(&self, id: &Identifier, e: String) -> PreprocessError {
+        self.error_at(
+            id,
+            format!("Invalid value for setting '{}' : {}", id.content.0, e),
+        )
+    }
+
+    /// Fold a constant expression, resolving previously defined macros
+    fn eval(&self, expr: &Expr, depth: usize) -> Result<Const, String> {
+        if depth > MAX_EVAL_DEPTH {
+            return Err("Expression is too deep, is there a recursive macro ?".to_string());
+        }
+        match expr {
+            Expr::BoolConst(value) => Ok(Const::Bool(*value)),
+            Expr::IntConst(value) => Ok(Const::Int(*value as i64)),
+            Expr::UIntConst(value) => Ok(Const::Int(*value as i64)),
+            Expr::FloatConst(value) => Ok(Const::Float(*value as f64)),
+            Expr::DoubleConst(value) => Ok(Const::Float(*value)),
+            Expr::Variable(ident) => self
+                .defines
+                .get(ident.content.0.as_str())
+                .ok_or_else(|| format!("'{}' is not a defined constant", ident.content.0))
+                .and_then(|it| self.eval(it, depth + 1)),
+            Expr::Unary(op, expr) => {
+                let value = self.eval(expr, depth + 1)?;
+                match (op, value) {
+                    (UnaryOp::Add, value) => Ok(value),
+                    (UnaryOp::Minus, Const::Int(value)) => {
+                        value.checked_neg().ok_or_else(overflow).map(Const::Int)
+                    }
+                    (UnaryOp::Minus, Const::Float(value)) => Ok(Const::Float(-value)),
+                    (UnaryOp::Minus, Const::Vector(values)) => {
+                        Ok(Const::Vector(values.into_iter().map(|it| -it).collect()))
+                    }
+                    (UnaryOp::Minus, Const::Matrix(values)) => {
+                        Ok(Const::Matrix(values.into_iter().map(|it| -it).collect()))
+                    }
+                    (UnaryOp::Not, Const::Bool(value)) => Ok(Const::Bool(!value)),
+                    _ => Err("Unsupported unary operation".to_string()),
+                }
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = self.eval(lhs, depth + 1)?;
+                let rhs = self.eval(rhs, depth + 1)?;
+                lhs.binary(op, rhs)
+            }
+            // Type constructors like float(1) or vec3(0.5)
+            Expr::FunCall(FunIdentifier::TypeSpecifier(TypeSpecifier { ty, .. }), args) => {
+                let mut components = Vec::new();
+                for arg in args.iter() {
+                    components.extend(self.eval(arg, depth + 1)?.components()?);
+                }
+                match ty {
+                    TypeSpecifierNonArray::Float | TypeSpecifierNonArray::Double => {
+                        components.first().map(|it| Const::Float(*it))
+                    }
+                    TypeSpecifierNonArray::Int | TypeSpecifierNonArray::UInt => {
+                        components.first().map(|it| Const::Int(*it as i64))
+                    }
+                    TypeSpecifierNonArray::Bool => {
+                        components.first().map(|it| Const::Bool(*it != 0.0))
+                    }
+                    TypeSpecifierNonArray::Mat2
+                    | TypeSpecifierNonArray::Mat3
+                    | TypeSpecifierNonArray::Mat4 => {
+                        let size = matrix_size(ty).unwrap();
+                        match components.len() {
+                            // Scalar on the diagonal
+                            1 => Some(Const::Matrix(diagonal(components[0], size))),
+                            n if n == size * size => Some(Const::Matrix(components)),
+                            _ => None,
+                        }
+                    }
+                    _ => vector_size(ty).and_then(|size| {
+                        let mut components = match components.len() {
+                            // Scalar splat
+                            1 => vec![components[0]; size],
+                            n if n >= size => components[..size].to_vec(),
+                            _ => return None,
+                        };
+                        // Integer vectors truncate their components
+                        if is_integer_vector(ty) {
+                            components.iter_mut().for_each(|it| *it = it.trunc());
+                        }
+                        Some(Const::Vector(components))
+                    }),
+                }
+                .ok_or_else(|| "Invalid constructor".to_string())
+            }
+            _ => Err("Not a constant expression".to_string()),
+        }
+    }
+
+    /// Get the value of a qualifier as a folded constant
+    fn constant(&self, id: &Identifier, param: Option<&Expr>) -> Result<Const, PreprocessError> {
+        self.eval(self.qualifier_value(id, param)?, 0)
+            .map_err(|e| self.invalid_value(id, e))
+    }
+
+    /// Get the value of a qualifier as a number
+    fn number<T: FromConst>(
+        &self,
+        id: &Identifier,
+        param: Option<&Expr>,
+    ) -> Result<T, PreprocessError> {
+        let value = match self.constant(id, param)? {
+            Const::Int(value) => T::from_int(value),
+            Const::Float(value) => T::from_const(value),
+            _ => {
+                return Err(
+                    self.error_at(id, format!("Setting '{}' expects a number", id.content.0))
+                )
+            }
+        };
+        value.map_err(|e| self.invalid_value(id, e))
+    }
+
+    /// Get the value of a qualifier as a vector of the given size, scalars are splat
+    fn vector(
+        &self,
+        id: &Identifier,
+        param: Option<&Expr>,
+        size: usize,
+    ) -> Result<Vec<f64>, PreprocessError> {
+        match self.constant(id, param)? {
+            Const::Vector(values) if values.len() == size => Ok(values),
+            Const::Int(value) => Ok(vec![value as f64; size]),
+            Const::Float(value) => Ok(vec![value; size]),
+            _ => Err(self.error_at(
+                id,
+                format!("Setting '{}' expects a vec{}", id.content.0, size),
+            )),
+        }
+    }
+
+    /// Get the value of a qualifier as the column major components of a square matrix,
+    /// scalars are put on the diagonal
+    fn matrix(
+        &self,
+        id: &Identifier,
+        param: Option<&Expr>,
+        size: usize,
+    ) -> Result<Vec<f32>, PreprocessError> {
+        let values = match self.constant(id, param)? {
+            Const::Matrix(values) if values.len() == size * size => values,
+            Const::Int(value) => diagonal(value as f64, size),
+            Const::Float(value) => diagonal(value, size),
+            _ => {
+                return Err(self.error_at(
+                    id,
+                    format!("Setting '{}' expects a mat{}", id.content.0, size),
+                ))
+            }
+        };
+        Ok(values.into_iter().map(|it| it as f32).collect())
+    }
+
+    /// Get the value of a qualifier as a boolean
+    fn boolean(&self, id: &Identifier, param: Option<&Expr>) -> Result<bool, PreprocessError> {
+        match self.constant(id, param)? {
+            Const::Bool(value) => Ok(value),
+            _ => Err(self.error_at(id, "Expected boolean value")),
+        }
+    }
+
+    /// Get the value of a qualifier as a string
+    fn string(&self, id: &Identifier, param: Option<&Expr>) -> Result<&str, PreprocessError> {
+        string_const(self.qualifier_value(id, param)?, &self.strings).ok_or_else(|| {
+            self.error_at(id, format!("Setting '{}' expects a string", id.content.0))
+        })
+    }
+
+    /// Create the param for a field and add it to the metadata
+    fn add_param_from_field(
+        &mut self,
+        field: &StructFieldSpecifier,
+    ) -> Result<(), PreprocessError> {
+        let mut param = Param::new(self.create_slider_from_field(field)?);
+
+        // The field span starts at its qualifiers
+        let start = field
+            .span
+            .as_ref()
+            .or_else(|| field.identifiers.first().unwrap().ident.span.as_ref())
+            .map(|span| usize::from(span.range().start()));
+        param.description = start.and_then(|start| doc_comment(&self.source, start));
+
+        let mut group_id = None;
+        for (id, value) in layout_qualifiers(field) {
+            match id.content.0.as_str() {
+                "group" => {
+                    param.group = Some(self.string(id, value)?.to_string());
+                    group_id = Some(id);
+                }
+                "label" => {
+                    param.label = Some(self.string(id, value)?.to_string());
+                }
+                _ => {}
+            }
+        }
+
+        self.metadata.add_param(param).map_err(|e| match group_id {
+            Some(id) => self.error_at(id, e),
+            None => self.error_at(&field.identifiers.first().unwrap().ident, e),
+        })
+    }
+
+    fn create_slider_from_field(
+        &self,
+        field: &StructFieldSpecifier,
+    ) -> Result<Slider, PreprocessError> {
+        let identifier = field.identifiers.first().unwrap();
+        let ident = &identifier.ident;
+        let name = ident.content.0.to_string();
+
+        //debug!("{:#?}", field);
+
+        // Arrays can be declared on the name (float a[8]) or on the type (float[8] a)
+        match identifier
+            .array_spec
+            .as_ref()
+            .or_else(|| field.ty.array_specifier.as_ref())
+        {
+            Some(_) if is_lut_field(field) => {
+                Err(self.error_at(ident, "Gradients and curves can't be arrays"))
+            }
+            Some(array) => {
+                let len = self.array_len(ident, array)?;
+                // Every item gets the qualifiers of the array
+                let items = (0..len)
+                    .map(|i| self.create_slider(field, ident, format!("{}[{}]", name, i)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Slider::Array { name, items })
+            }
+            None => self.create_slider(field, ident, name),
+        }
+    }
+
+    /// Get the size of an array param, only sized one dimensional arrays are supported
+    fn array_len(
+        &self,
+        ident: &Identifier,
+        array: &ArraySpecifier,
+    ) -> Result<usize, PreprocessError> {
+        match array.dimensions.0.as_slice() {
+            [ArraySpecifierDimension::ExplicitlySized(size)] => match self.eval(size, 0) {
+                Ok(Const::Int(len)) if len > 0 => Ok(len as usize),
+                _ => Err(self.error_at(ident, "Array size must be a positive constant")),
+            },
+            [ArraySpecifierDimension::Unsized] => {
+                Err(self.error_at(ident, "Arrays in params block must be sized"))
+            }
+            _ => Err(self.error_at(ident, "Arrays of arrays are not supported in params block")),
+        }
+    }
+
+    /// Create the slider for a single value of the field type
+    fn create_slider(
+        &self,
+        field: &StructFieldSpecifier,
+        ident: &Identifier,
+        name: String,
+    ) -> Result<Slider, PreprocessError> {
+        let unsupported = |id: &Identifier| {
+            self.error_at(
+                id,
+                format!("Unsupported setting '{}' on param '{}'", id.content.0, name),
+            )
+        };
+
+        if is_lut_field(field) {
+            return self.create_lut_slider(field, ident, name);
+        }
+
+        match field.ty.ty {
+            // To Slider::Float
+            TypeSpecifierNonArray::Float => {
+                let mut min = 0.0;
+                let mut max = 1.0;
+                let mut step = 0.0;
+                let mut log = false;
+                let mut init = 0.0;
+
+                for (id, param) in slider_qualifiers(field) {
+                    match id.content.0.as_str() {
+                        "min" => {
+                            min = self.number(id, param)?;
+                        }
+                        "max" => {
+                            max = self.number(id, param)?;
+                        }
+                        "step" => {
+                            step = self.number(id, param)?;
+                            if step < 0.0 {
+                                return Err(self.error_at(id, "Step can't be negative"));
+                            }
+                        }
+                        "log" => {
+                            log = true;
+                        }
+                        "init" => {
+                            init = self.number(id, param)?;
+                        }
+                        _ => return Err(unsupported(id)),
+                    }
+                }
+                Ok(Slider::Float {
+                    name,
+                    min,
+                    max,
+                    step,
+                    log,
+                    value: init,
+                    default: init,
+                })
+            }
+            // To Slider::Int or to Slider::Enum if options are given
+            TypeSpecifierNonArray::Int => {
+                let mut min = 0;
+                let mut max = 100;
+                let mut step = 1;
+                let mut init = None;
+                let mut options: Option<Vec<String>> = None;
+
+                for (id, param) in slider_qualifiers(field) {
+                    match id.content.0.as_str() {
+                        "min" => {
+                            min = self.number(id, param)?;
+                        }
+                        "max" => {
+                            max = self.number(id, param)?;
+                        }
+                        "step" => {
+                            step = self.number(id, param)?;
+                        }
+                        "init" => {
+                            init = Some((id, self.qualifier_value(id, param)?));
+                        }
+                        "options" => {
+                            options = Some(
+                                self.string(id, param)?
+                                    .split(',')
+                                    .map(|it| it.trim().to_string())
+                                    .filter(|it| !it.is_empty())
+                                    .collect(),
+                            );
+                        }
+                        _ => return Err(unsupported(id)),
+                    }
+                }
+                if let Some(options) = options {
+                    // The initial value can be given as an index or as one of the options name
+                    let init = match init {
+                        Some((_, Expr::Variable(option)))
+                            if options.iter().any(|it| it == option.content.0.as_str()) =>
+                        {
+                            options
+                                .iter()
+                                .position(|it| it == option.content.0.as_str())
+                                .unwrap() as i32
+                        }
+                        Some((id, expr)) => self.number(id, Some(expr))?,
+                        None => 0,
+                    };
+                    return Ok(Slider::Enum {
+                        name,
+                        options,
+                        value: init,
+                        default: init,
+                    });
+                }
+                let init = match init {
+                    Some((id, expr)) => self.number(id, Some(expr))?,
+                    None => 0,
+                };
+                Ok(Slider::Int {
+                    name,
+                    min,
+                    max,
+                    step: step.max(1),
+                    value: init,
+                    default: init,
+                })
+            }
+            // To Slider::UInt
+            TypeSpecifierNonArray::UInt => {
+                let mut min = 0;
+                let mut max = 100;
+                let mut step = 1;
+                let mut init = 0;
+
+                for (id, param) in slider_qualifiers(field) {
+                    match id.content.0.as_str() {
+                        "min" => {
+                            min = self.number(id, param)?;
+                        }
+                        "max" => {
+                            max = self.number(id, param)?;
+                        }
+                        "step" => {
+                            step = self.number(id, param)?;
+                        }
+                        "init" => {
+                            init = self.number(id, param)?;
+                        }
+                        _ => return Err(unsupported(id)),
+                    }
+                }
+                Ok(Slider::UInt {
+                    name,
+                    min,
+                    max,
+                    step: step.max(1),
+                    value: init,
+                    default: init,
+                })
+            }
+            // To Slider::Vec2, Slider::Vec3 or Slider::Vec4,
+            // or to Slider::Color and Slider::ColorAlpha if color layout qualifier is set
+            TypeSpecifierNonArray::Vec2
+            | TypeSpecifierNonArray::Vec3
+            | TypeSpecifierNonArray::Vec4 => {
+                let size = vector_size(&field.ty.ty).unwrap();
+                let mut init = vec![0.0; size];
+                // Unbounded by default
+                let mut min = vec![f64::NEG_INFINITY; size];
+                let mut max = vec![f64::INFINITY; size];
+                let mut speed = vec![0.01; size];
+                let mut color = None;
+                // A qualifier only meaningful for drag values
+                let mut drag_qualifier = None;
+                let mut pad = false;
+                let mut canvas = None;
+                let mut direction = false;
+                let mut euler = false;
+
+                for (id, param) in slider_qualifiers(field) {
+                    match id.content.0.as_str() {
+                        "color" if size > 2 => {
+                            color = Some(id);
+                        }
+                        "init" => {
+                            init = self.vector(id, param, size)?;
+                        }
+                        "min" => {
+                            min = self.vector(id, param, size)?;
+                            drag_qualifier = Some(id);
+                        }
+                        "max" => {
+                            max = self.vector(id, param, size)?;
+                            drag_qualifier = Some(id);
+                        }
+                        "speed" => {
+                            speed = self.vector(id, param, size)?;
+                            drag_qualifier = Some(id);
+                        }
+                        "direction" if size == 3 => {
+                            direction = true;
+                        }
+                        "euler" if size == 3 => {
+                            euler = true;
+                        }
+                        "xy" if size == 2 => {
+                            pad = true;
+                        }
+                        "canvas" if size == 2 => {
+                            let coords = match param {
+                                None => CanvasCoords::Pixels,
+                                Some(Expr::Variable(coords)) => match coords.content.0.as_str() {
+                                    "pixels" => CanvasCoords::Pixels,
+                                    "normalized" => CanvasCoords::Normalized,
+                                    _ => {
+                                        return Err(self.error_at(
+                                            id,
+                                            "Setting 'canvas' expects pixels or normalized",
+                                        ))
+                                    }
+                                },
+                                _ => {
+                                    return Err(self.error_at(
+                                        id,
+                                        "Setting 'canvas' expects pixels or normalized",
+                                    ))
+                                }
+                            };
+                            canvas = Some((id, coords));
+                        }
+                        _ => return Err(unsupported(id)),
+                    }
+                }
+                if let (Some(_), Some(id)) = (color, drag_qualifier) {
+                    return Err(unsupported(id));
+                }
+                // Only one kind of widget
+                if [color.is_some(), direction, euler]
+                    .iter()
+                    .filter(|it| **it)
+                    .count()
+                    > 1
+                {
+                    return Err(self.error_at(
+                        ident,
+                        format!(
+                            "Param '{}' can only be one of color, direction or euler",
+                            name
+                        ),
+                    ));
+                }
+                if let (true, Some(id)) = (direction || euler, drag_qualifier) {
+                    return Err(unsupported(id));
+                }
+                if let (false, Some((id, _))) = (pad, canvas) {
+                    return Err(self.error_at(id, "Setting 'canvas' needs the 'xy' setting"));
+                }
+
+                let v2 = |v: &[f64]| Vector2::from([v[0] as f32, v[1] as f32]);
+                let v3 = |v: &[f64]| Vector3::from([v[0] as f32, v[1] as f32, v[2] as f32]);
+                let v4 =
+                    |v: &[f64]| Vector4::from([v[0] as f32, v[1] as f32, v[2] as f32, v[3] as f32]);
+                Ok(match (size, color) {
+                    (2, _) if pad => Slider::pad(
+                        name,
+                        v2(&min),
+                        v2(&max),
+                        v2(&speed),
+                        canvas.map(|(_, coords)| coords),
+                        v2(&init),
+                    ),
+                    (2, _) => Slider::Vec2 {
+                        name,
+                        min: v2(&min),
+                        max: v2(&max),
+                        speed: v2(&speed),
+                        value: v2(&init),
+                        default: v2(&init),
+                    },
+                    (3, Some(_)) => Slider::Color {
+                        name,
+                        value: v3(&init),
+                        default: v3(&init),
+                    },
+                    (3, None) if direction => Slider::direction(name, v3(&init)),
+                    (3, None) if euler => Slider::Euler {
+                        name,
+                        value: v3(&init),
+                        default: v3(&init),
+                    },
+                    (3, None) => Slider::Vec3 {
+                        name,
+                        min: v3(&min),
+                        max: v3(&max),
+                        speed: v3(&speed),
+                        value: v3(&init),
+                        default: v3(&init),
+                    },
+                    (_, Some(_)) => Slider::ColorAlpha {
+                        name,
+                        value: v4(&init),
+                        default: v4(&init),
+                    },
+                    (_, None) => Slider::Vec4 {
+                        name,
+                        min: v4(&min),
+                        max: v4(&max),
+                        speed: v4(&speed),
+                        value: v4(&init),
+                        default: v4(&init),
+                    },
+                })
+            }
+            // To Slider::IVec2, Slider::IVec3, Slider::UVec2 or Slider::UVec3
+            TypeSpecifierNonArray::IVec2
+            | TypeSpecifierNonArray::IVec3
+            | TypeSpecifierNonArray::UVec2
+            | TypeSpecifierNonArray::UVec3 => {
+                let size = match field.ty.ty {
+                    TypeSpecifierNonArray::IVec2 | TypeSpecifierNonArray::UVec2 => 2,
+                    _ => 3,
+                };
+                // Same defaults as int and uint
+                let mut init = vec![0.0; size];
+                let mut min = vec![0.0; size];
+                let mut max = vec![100.0; size];
+                let mut step: u32 = 1;
+                let unsigned = matches!(
+                    field.ty.ty,
+                    TypeSpecifierNonArray::UVec2 | TypeSpecifierNonArray::UVec3
+                );
+                // Components must fit the integer type, the casts below are exact
+                let integers = |id: &Identifier, values: Vec<f64>| -> Result<_, PreprocessError> {
+                    for value in &values {
+                        let fits = if unsigned {
+                            u32::from_const(*value).map(|_| ())
+                        } else {
+                            i32::from_const(*value).map(|_| ())
+                        };
+                        fits.map_err(|e| self.invalid_value(id, e))?;
+                    }
+                    Ok(values)
+                };
+
+                for (id, param) in slider_qualifiers(field) {
+                    match id.content.0.as_str() {
+                        "init" => {
+                            init = integers(id, self.vector(id, param, size)?)?;
+                        }
+                        "min" => {
+                            min = integers(id, self.vector(id, param, size)?)?;
+                        }
+                        "max" => {
+                            max = integers(id, self.vector(id, param, size)?)?;
+                        }
+                        "step" => {
+                            step = self.number(id, param)?;
+                            if !unsigned {
+                                integers(id, vec![step as f64])?;
+                            }
+                        }
+                        _ => return Err(unsupported(id)),
+                    }
+                }
+                let step = step.max(1);
+                let i2 = |v: &[f64]| Vector2::from([v[0] as i32, v[1] as i32]);
+                let i3 = |v: &[f64]| Vector3::from([v[0] as i32, v[1] as i32, v[2] as i32]);
+                let u2 = |v: &[f64]| Vector2::from([v[0] as u32, v[1] as u32]);
+                let u3 = |v: &[f64]| Vector3::from([v[0] as u32, v[1] as u32, v[2] as u32]);
+                Ok(match field.ty.ty {
+                    TypeSpecifierNonArray::IVec2 => Slider::IVec2 {
+                        name,
+                        min: i2(&min),
+                        max: i2(&max),
+                        step: step as i32,
+                        value: i2(&init),
+                        default: i2(&init),
+                    },
+                    TypeSpecifierNonArray::IVec3 => Slider::IVec3 {
+                        name,
+                        min: i3(&min),
+                        max: i3(&max),
+                        step: step as i32,
+                        value: i3(&init),
+                        default: i3(&init),
+                    },
+                    TypeSpecifierNonArray::UVec2 => Slider::UVec2 {
+                        name,
+                        min: u2(&min),
+                        max: u2(&max),
+                        step,
+                        value: u2(&init),
+                        default: u2(&init),
+                    },
+                    _ => Slider::UVec3 {
+                        name,
+                        min: u3(&min),
+                        max: u3(&max),
+                        step,
+                        value: u3(&init),
+                        default: u3(&init),
+                    },
+                })
+            }
+            // To Slider::Mat2, Slider::Mat3 or Slider::Mat4
+            TypeSpecifierNonArray::Mat2
+            | TypeSpecifierNonArray::Mat3
+            | TypeSpecifierNonArray::Mat4 => {
+                let size = matrix_size(&field.ty.ty).unwrap();
+                // Identity by default
+                let mut init: Vec<f32> = diagonal(1.0, size)
+                    .into_iter()
+                    .map(|it| it as f32)
+                    .collect();
+
+                for (id, param) in slider_qualifiers(field) {
+                    match id.content.0.as_str() {
+                        "init" => {
+                            init = self.matrix(id, param, size)?;
+                        }
+                        _ => return Err(unsupported(id)),
+                    }
+                }
+                let c = |i: usize| &init[i * size..(i + 1) * size];
+                Ok(match size {
+                    2 => {
+                        let init = ColumnMatrix2::from([[c(0)[0], c(0)[1]], [c(1)[0], c(1)[1]]]);
+                        Slider::Mat2 {
+                            name,
+                            value: init,
+                            default: init,
+                        }
+                    }
+                    3 => {
+                        let init = ColumnMatrix3::from([
+                            [c(0)[0], c(0)[1], c(0)[2]],
+                            [c(1)[0], c(1)[1], c(1)[2]],
+                            [c(2)[0], c(2)[1], c(2)[2]],
+                        ]);
+                        Slider::Mat3 {
+                            name,
+                            value: init,
+                            default: init,
+                        }
+                    }
+                    _ => {
+                        let init = ColumnMatrix4::from([
+                            [c(0)[0], c(0)[1], c(0)[2], c(0)[3]],
+                            [c(1)[0], c(1)[1], c(1)[2], c(1)[3]],
+                            [c(2)[0], c(2)[1], c(2)[2], c(2)[3]],
+                            [c(3)[0], c(3)[1], c(3)[2], c(3)[3]],
+                        ]);
+                        Slider::Mat4 {
+                            name,
+                            value: init,
+                            default: init,
+                        }
+                    }
+                })
+            }
+            TypeSpecifierNonArray::Bool => {
+                let mut init = 0;
+                let mut key = None;
+                let mut hold = false;
+
+                for (id, param) in slider_qualifiers(field) {
+                    match id.content.0.as_str() {
+                        "init" => {
+                            init = if self.boolean(id, param)? { 1 } else { 0 };
+                        }
+                        "key" => {
+                            let name = self.string(id, param)?;
+                            key = Some(input::key_from_name(name).ok_or_else(|| {
+                                self.error_at(id, format!("Unknown key '{}'", name))
+                            })?);
+                        }
+                        "hold" => {
+                            hold = true;
+                        }
+                        _ => return Err(unsupported(id)),
+                    }
+                }
+                if hold && key.is_none() {
+                    return Err(self.error_at(ident, "Setting 'hold' needs a key"));
+                }
+                Ok(Slider::Bool {
+                    name,
+                    key: key.map(|key| KeyBinding { key, hold }),
+                    value: init,
+                    default: init,
+                })
+            }
+            _ => Err(self.error_at(
+                ident,
+                format!("Unsupported type for param '{}' in params block", name),
+            )),
+        }
+    }
+}
+
+impl Extractor {
+    /// Create the slider for a gradient or a curve field
+    fn create_lut_slider(
+        &self,
+        field: &StructFieldSpecifier,
+        ident: &Identifier,
+        name: String,
+    ) -> Result<Slider, PreprocessError> {
+        let mut gradient = false;
+        let mut curve = false;
+        let mut min = 0.0;
+        let mut max = 1.0;
+        let mut init = None;
+
+        for (id, param) in slider_qualifiers(field) {
+            match id.content.0.as_str() {
+                "gradient" => gradient = true,
+                "curve" => curve = true,
+                "min" if curve => {
+                    min = self.number(id, param)?;
+                }
+                "max" if curve => {
+                    max = self.number(id, param)?;
+                }
+                "init" => {
+                    init = Some((id, self.string(id, param)?));
+                }
+                _ => {
+                    return Err(self.error_at(
+                        id,
+                        format!("Unsupported setting '{}' on param '{}'", id.content.0, name),
+                    ))
+                }
+            }
+        }
+
+        match (&field.ty.ty, gradient, curve) {
+            (TypeSpecifierNonArray::Vec3, true, false)
+            | (TypeSpecifierNonArray::Vec4, true, false) => {
+                let stops = match init {
+                    Some((id, init)) => parse_gradient(init).map_err(|e| {
+                        self.error_at(id, format!("Invalid gradient '{}' : {}", init, e))
+                    })?,
+                    None => vec![
+                        GradientStop {
+                            position: 0.0,
+                            color: [0.0, 0.0, 0.0, 1.0].into(),
+                        },
+                        GradientStop {
+                            position: 1.0,
+                            color: [1.0, 1.0, 1.0, 1.0].into(),
+                        },
+                    ],
+                };
+                Ok(Slider::Gradient {
+                    name,
+                    alpha: matches!(field.ty.ty, TypeSpecifierNonArray::Vec4),
+                    value: stops.clone(),
+                    default: stops,
+                })
+            }
+            (TypeSpecifierNonArray::Float, false, true) => {
+                let points = match init {
+                    Some((id, init)) => parse_curve(init).map_err(|e| {
+                        self.error_at(id, format!("Invalid curve '{}' : {}", init, e))
+                    })?,
+                    None => vec![[0.0, min].into(), [1.0, max].into()],
+                };
+                Ok(Slider::Curve {
+                    name,
+                    min,
+                    max,
+                    value: points.clone(),
+                    default: points,
+                })
+            }
+            (_, true, true) => Err(self.error_at(ident, "A param can't be a gradient and a curve")),
+            (_, true, _) => Err(self.error_at(ident, "Gradients must be vec3 or vec4")),
+            _ => Err(self.error_at(ident, "Curves must be float")),
+        }
+    }
+}
+
+/// Parse gradient stops written as `position #rrggbb[aa]`, separated by commas
+fn parse_gradient(init: &str) -> Result<Vec<GradientStop>, String> {
+    let mut stops = init
+        .split(',')
+        .map(|stop| {
+            let mut parts = stop.split_whitespace();
+            let position = parts
+                .next()
+                .and_then(|it| it.parse::<f32>().ok())
+                .ok_or("expected a position")?;
+            let color = parts
+                .next()
+                .and_then(parse_hex_color)
+                .ok_or("expected a color like #ff8000")?;
+            Ok(GradientStop {
+                position: position.max(0.0).min(1.0),
+                color,
+            })
+        })
+        .collect::<Result<Vec<_>, &str>>()?;
+    stops.sort_by(|a, b| a.position.partial_cmp(&b.position).unwrap());
+    Ok(stops)
+}
+
+/// Parse a #rrggbb or #rrggbbaa color
+fn parse_hex_color(hex: &str) -> Option<Vector4<f32>> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 && hex.len() != 8 {
+        return None;
+    }
+    let mut color = [1.0; 4];
+    for (i, c) in color.iter_mut().enumerate().take(hex.len() / 2) {
+        *c = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()? as f32 / 255.0;
+    }
+    Some(color.into())
+}
+
+/// Parse curve points written as `x y`, separated by commas
+fn parse_curve(init: &str) -> Result<Vec<Vector2<f32>>, String> {
+    let mut points = init
+        .split(',')
+        .map(|point| {
+            let coords = point
+                .split_whitespace()
+                .map(|it| it.parse::<f32>().map_err(|_| "expected numbers"))
+                .collect::<Result<Vec<_>, _>>()?;
+            match coords.as_slice() {
+                [x, y] => Ok(Vector2::from([x.max(0.0).min(1.0), *y])),
+                _ => Err("expected a point like '0.5 1.0'"),
+            }
+        })
+        .collect::<Result<Vec<_>, &str>>()?;
+    points.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+    Ok(points)
+}
+
+/// Bindings and sampling function of a gradient or a curve, the texture is linearly
+/// interpolated by hand since float textures can't be filtered
+fn lut_declarations(name: &str, return_ty: &str, swizzle: &str, binding: usize) -> String {
+    format!(
+        r"layout(set = 2, binding = {binding}) uniform texture1D {name}_lut;
+{ty} {name}(float t) {{
+    float x = clamp(t, 0.0, 1.0) * {last}.0;
+    int i = int(x);
+    vec4 a = texelFetch(sampler1D({name}_lut, nuanceLutSampler), i, 0);
+    vec4 b = texelFetch(sampler1D({name}_lut, nuanceLutSampler), min(i + 1, {last}), 0);
+    return mix(a, b, fract(x)){swizzle};
+}}
+",
+        binding = binding,
+        name = name,
+        ty = return_ty,
+        last = LUT_SIZE - 1,
+        swizzle = swizzle,
+    )
+}
+
+/// true if the field is a gradient or a curve
+fn is_lut_field(field: &StructFieldSpecifier) -> bool {
+    layout_qualifiers(field).any(|(id, _)| LUT_QUALIFIERS.contains(&id.content.0.as_str()))
+}
+
+/// true if the block is the one with the layout(params) qualifier
+fn is_params_block(block: &Block) -> bool {
+    if let Some(TypeQualifierSpec::Layout(layout)) = block.qualifier.qualifiers.first() {
+        if let Some(LayoutQualifierSpec::Identifier(id, _)) = layout.ids.first() {
+            return id.content.0 == "params";
+        }
+    }
+    false
+}
+
+/// Iterate over the layout(...) qualifiers of a field, with their optional value
+fn layout_qualifiers(
+    field: &StructFieldSpecifier,
+) -> impl Iterator<Item = (&Identifier, Option<&Expr>)> {
+    field
+        .qualifier
+        .iter()
+        .flat_map(|TypeQualifier { qualifiers }| qualifiers.iter())
+        .filter_map(|qualifier| {
+            if let TypeQualifierSpec::Layout(LayoutQualifier { ids }) = qualifier {
+                Some(ids.iter())
+            } else {
+                None
+            }
+        })
+        .flatten()
+        .filter_map(|qualifier| {
+            if let LayoutQualifierSpec::Identifier(id, param) = qualifier {
+                Some((id, param.as_deref()))
+            } else {
+                debug!("Ignoring shared qualifier");
+                None
+            }
+        })
+}
+
+/// Iterate over the layout(...) qualifiers specific to the field type, without the common ones
+fn slider_qualifiers(
+    field: &StructFieldSpecifier,
+) -> impl Iterator<Item = (&Identifier, Option<&Expr>)> {
+    layout_qualifiers(field).filter(|(id, _)| !COMMON_QUALIFIERS.contains(&id.content.0.as_str()))
+}
+
+/// Replace the layout(params) with a predefined layout(set=?, binding=?)
+pub fn convert_params_block(block: &mut Block) {
+    block.qualifier.qualifiers[0] = TypeQualifierSpec::Layout(LayoutQualifier {
+        ids: vec![
+            LayoutQualifierSpec::Identifier(
+                Identifier {
+                    content: IdentifierData(SmolStr::new("std140")),
+                    span: None,
+                },
+                None,
+            ),
+            LayoutQualifierSpec::Identifier(
+                Identifier {
+                    content: IdentifierData(SmolStr::new("set")),
+                    span: None,
+                },
+                Some(Box::new(Expr::IntConst(1))),
+            ),
+            LayoutQualifierSpec::Identifier(
+                Identifier {
+                    content: IdentifierData(SmolStr::new("binding")),
+                    span: None,
+                },
+                Some(Box::new(Expr::IntConst(0))),
+            ),
+        ],
+    });
+}
+
+/// Replace the layout(min=?, max=?) with nothing
+pub fn convert_field(field: &mut StructFieldSpecifier) {
+    field.qualifier = None;
+}
+
+/// Glsl has no string literals, so we swap the ones used as layout qualifier values
+/// (e.g. `layout(options = "a,b")`) with placeholder identifiers the parser can understand.
+/// Placeholders are padded to the string length when possible to keep error columns right.
+/// Returns the modified source and the extracted strings, indexed by placeholder number.
+fn extract_strings(source: &str) -> (String, Vec<String>) {
+    let mut output = String::with_capacity(source.len());
+    let mut strings = Vec::new();
+    let mut rest = source;
+
+    while let Some(pos) = rest.find("layout") {
+        let (before, after) = rest.split_at(pos);
+        output.push_str(before);
+        output.push_str("layout");
+        rest = &after["layout".len()..];
+
+        let trimmed = rest.trim_start();
+        if !trimmed.starts_with('(') {
+            continue;
+        }
+        // Copy the qualifier list up to the matching parenthesis
+        output.push_str(&rest[..rest.len() - trimmed.len()]);
+        rest = trimmed;
+        let mut depth = 0;
+        let mut chars = rest.char_indices();
+        let mut end = rest.len();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = i + 1;
+                        break;
+                    }
+                }
+                '"' => {
+                    let start = i + 1;
+                    let len = rest[start..].find('"').unwrap_or(rest.len() - start);
+                    let literal = &rest[start..(start + len + 1).min(rest.len())];
+                    let placeholder = format!("{}{}", STRING_PLACEHOLDER, strings.len());
+                    // + 1 for the opening quote
+                    let padding = (literal.chars().count() + 1).saturating_sub(placeholder.len());
+                    output.push_str(&placeholder);
+                    output.extend(std::iter::repeat(' ').take(padding));
+                    strings.push(rest[start..start + len].to_string());
+                    // Skip the string content and the closing quote
+                    for _ in 0..literal.chars().count() {
+                        chars.next();
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            output.push(c);
+        }
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+
+    (output, strings)
+}
+
+/// Get the string value of a qualifier, if it was a string literal
+fn string_const<'a>(expr: &Expr, strings: &'a [String]) -> Option<&'a str> {
+    if let Expr::Variable(ident) = expr {
+        ident
+            .content
+            .0
+            .strip_prefix(STRING_PLACEHOLDER)
+            .and_then(|it| it.parse::<usize>().ok())
+            .and_then(|it| strings.get(it))
+            .map(String::as_str)
+    } else {
+        None
+    }
+}
+
+/// Get the `///` comment lines right before the line at the given offset
+fn doc_comment(source: &str, offset: usize) -> Option<String> {
+    let line_start = source[..offset.min(source.len())]
+        .rfind('\n')
+        .map_or(0, |it| it + 1);
+    let mut lines: Vec<&str> = source[..line_start]
+        .lines()
+        .rev()
+        .map(str::trim)
+        .take_while(|it| it.starts_with("///"))
+        .map(|it| it.trim_start_matches('/').trim())
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+    lines.reverse();
+    Some(lines.join("\n"))
+}
+
+/// Convert a byte offset in the source to a line and column, starting at 1
+fn position(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |it| it + 1) + 1;
+    (line, column)
+}
+
+/// Whether the source declares a params block. This only looks at the text, so it works on
+/// sources the parser can't handle.
+pub fn has_params_block(source: &str) -> bool {
+    let source: String = source.split_whitespace().collect();
+    source.match_indices("layout(").any(|(i, it)| {
+        source[i + it.len()..]
+            .split(')')
+            .next()
+            .map_or(false, |qualifiers| {
+                qualifiers.split(',').any(|it| it == "params")
+            })
+    })
+}
+
+/// Extract the params from a glsl source and transpile it to valid glsl.
+/// `file` is only used for error reporting.
+pub fn extract(source: &str, file: &str) -> Result<(ShaderMetadata, String), PreprocessError> {
+    let (source, strings) = extract_strings(source);
+
+    let opts = ParseOptions {
+        target_vulkan: true,
+        source_id: 0,
+        allow_rs_ident: false,
+    }
+    .build();
+
+    // The AST
+    let (mut ast, _ctx) = TranslationUnit::parse_with_options(&source, &opts).map_err(|e| {
+        let (line, column) = position(&source, usize::from(e.pos().range().start()));
+        PreprocessError {
+            file: file.to_string(),
+            line,
+            column,
+            message: e.to_string(),
+        }
+    })?;
+
+    // Where the params block is declared, generated declarations go right after
+    let params_pos = ast.0.iter().position(|it| {
+        matches!(it, ExternalDeclaration::Declaration(Declaration::Block(block)) if is_params_block(block))
+    });
+
+    let mut extractor = Extractor {
+        file: file.to_string(),
+        source,
+        strings,
+        ..Default::default()
+    };
+
+    // Extract some ast juice
+    ast.visit_mut(&mut extractor);
+    if let Some(error) = extractor.errors.into_iter().next() {
+        return Err(error);
+    }
+    let metadata = extractor.metadata;
+
+    // Named constants for enum params
+    let mut generated = String::new();
+    for slider in metadata.sliders() {
+        let enumeration = match slider {
+            Slider::Enum { name, options, .. } => Some((name, options)),
+            // Items of an array share their options, constants are named after the array
+            Slider::Array { name, items } => match items.first() {
+                Some(Slider::Enum { options, .. }) => Some((name, options)),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some((name, options)) = enumeration {
+            for (i, option) in options.iter().enumerate() {
+                generated.push_str(&format!("#define {}_{} {}\n", name, option, i));
+            }
+        }
+    }
+    // Sampling functions for gradients and curves, binding 0 is the shared sampler
+    let mut luts = String::new();
+    for slider in metadata.sliders() {
+        let (name, ty, swizzle) = match slider {
+            Slider::Gradient {
+                name, alpha: true, ..
+            } => (name, "vec4", ""),
+            Slider::Gradient { name, .. } => (name, "vec3", ".rgb"),
+            Slider::Curve { name, .. } => (name, "float", ".r"),
+            _ => continue,
+        };
+        if luts.is_empty() {
+            luts.push_str("layout(set = 2, binding = 0) uniform sampler nuanceLutSampler;\n");
+        }
+        let binding = luts.matches("uniform texture1D").count() + 1;
+        luts.push_str(&lut_declarations(name, ty, swizzle, binding));
+    }
+    generated.push_str(&luts);
+
+    if let Some(pos) = params_pos {
+        // The block is removed when it only had gradients and curves, it would be invalid glsl
+        let empty = matches!(&ast.0[pos], ExternalDeclaration::Declaration(Declaration::Block(block)) if block.fields.is_empty());
+        let insert = if empty {
+            ast.0.remove(pos);
+            pos
+        } else {
+            pos + 1
+        };
+        if !generated.is_empty() {
+            let (generated, _) =
+                TranslationUnit::parse_with_options(&generated, &opts).map_err(|e| {
+                    PreprocessError {
+                        file: file.to_string(),
+                        line: 0,
+                        column: 0,
+                        message: format!("Invalid option or param name : {}", e),
+                    }
+                })?;
+            ast.0.splice(insert..insert, generated.0);
+        }
+    }
+
+    let mut transpiled = String::new();
+    glsl_lang::transpiler::glsl::show_translation_unit(
+        &mut transpiled,
+        &ast,
+        FormattingState::default(),
+    )
+    .map_err(|e| PreprocessError {
+        file: file.to_string(),
+        line: 0,
+        column: 0,
+        message: e.to_string(),
+    })?;
+    Ok((metadata, transpiled))
+}
+
+/// Number of components of a vector type
+fn vector_size(ty: &TypeSpecifierNonArray) -> Option<usize> {
+    match ty {
+        TypeSpecifierNonArray::Vec2
+        | TypeSpecifierNonArray::IVec2
+        | TypeSpecifierNonArray::UVec2 => Some(2),
+        TypeSpecifierNonArray::Vec3
+        | TypeSpecifierNonArray::IVec3
+        | TypeSpecifierNonArray::UVec3 => Some(3),
+        TypeSpecifierNonArray::Vec4
+        | TypeSpecifierNonArray::IVec4
+        | TypeSpecifierNonArray::UVec4 => Some(4),
+        _ => None,
+    }
+}
+
+/// Constructor expression of a vector or matrix type, components of matrices are column major
+fn constructor_expr<'a>(
+    ty: TypeSpecifierNonArray,
+    components: impl Iterator<Item = &'a f32>,
+) -> Expr {
+    Expr::FunCall(
+        FunIdentifier::TypeSpecifier(TypeSpecifier {
+            ty,
+            array_specifier: None,
+        }),
+        components.map(|it| Expr::FloatConst(*it)).collect(),
+    )
+}
+
+/// Properties of scalar params, named in the error for an unknown property
+const SCALAR_PROPERTIES: &str = "'min', 'max', 'init' or 'step'";
+
+/// Expression replacing the `.init`, `.min`, `.max` or `.step` property of an integer vector
+/// param, given as (init, min, max, step). Returns None for other properties as they may be
+/// swizzles.
+fn integer_vector_property<T: Copy>(
+    property: &str,
+    ty: TypeSpecifierNonArray,
+    (init, min, max, step): (&[T], &[T], &[T], T),
+    constant: impl Fn(T) -> Expr,
+) -> Option<Expr> {
+    let components = match property {
+        "init" => init,
+        "min" => min,
+        "max" => max,
+        "step" => return Some(constant(step)),
+        _ => return None,
+    };
+    Some(Expr::FunCall(
+        FunIdentifier::TypeSpecifier(TypeSpecifier {
+            ty,
+            array_specifier: None,
+        }),
+        components.iter().map(|it| constant(*it)).collect(),
+    ))
+}
+
+/// Expression replacing the `.init`, `.min` or `.max` property of a vector param.
+/// Returns None for other properties as they may be swizzles.
+fn vector_property(
+    property: &str,
+    ty: TypeSpecifierNonArray,
+    init: &[f32],
+    min: &[f32],
+    max: &[f32],
+) -> Result<Option<Expr>, String> {
+    let components = match property {
+        "init" => init,
+        "min" => min,
+        "max" => max,
+        _ => return Ok(None),
+    };
+    // Unbounded components can't be written as glsl constants
+    if components.iter().any(|it| it.is_infinite()) {
+        return Err(format!("Vector param has no '{}' bound", property));
+    }
+    Ok(Some(constructor_expr(ty, components.iter())))
+}
+
+/// Number of columns of a square matrix type
+fn matrix_size(ty: &TypeSpecifierNonArray) -> Option<usize> {
+    match ty {
+        TypeSpecifierNonArray::Mat2 => Some(2),
+        TypeSpecifierNonArray::Mat3 => Some(3),
+        TypeSpecifierNonArray::Mat4 => Some(4),
+        _ => None,
+    }
+}
+
+/// Column major components of a square matrix with the value on its diagonal
+fn diagonal(value: f64, size: usize) -> Vec<f64> {
+    (0..size * size)
+        .map(|i| if i % (size + 1) == 0 { value } else { 0.0 })
+        .collect()
+}
+
+fn is_integer_vector(ty: &TypeSpecifierNonArray) -> bool {
+    matches!(
+        ty,
+        TypeSpecifierNonArray::IVec2
+            | TypeSpecifierNonArray::IVec3
+            | TypeSpecifierNonArray::IVec4
+            | TypeSpecifierNonArray::UVec2
+            | TypeSpecifierNonArray::UVec3
+            | TypeSpecifierNonArray::UVec4
+    )
+}
+
+/// A constant value folded from a qualifier expression
+#[derive(Debug, Clone, PartialEq)]
+enum Const {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Vector(Vec<f64>),
+    /// Column major square matrix
+    Matrix(Vec<f64>),
+}
+
+impl Const {
+    /// Flatten to a list of components, used by constructors
+    fn components(self) -> Result<Vec<f64>, String> {
+        match self {
+            Const::Bool(value) => Ok(vec![if value { 1.0 } else { 0.0 }]),
+            Const::Int(value) => Ok(vec![value as f64]),
+            Const::Float(value) => Ok(vec![value]),
+            Const::Vector(values) | Const::Matrix(values) => Ok(values),
+        }
+    }
+
+    fn binary(self, op: &BinaryOp, rhs: Const) -> Result<Const, String> {
+        match (self, rhs) {
+            // Integer arithmetic stays integer
+            (Const::Int(a), Const::Int(b)) => match op {
+                BinaryOp::Div | BinaryOp::Mod if b == 0 => Err("Division by zero".to_string()),
+                BinaryOp::Add => a.checked_add(b).ok_or_else(overflow).map(Const::Int),
+                BinaryOp::Sub => a.checked_sub(b).ok_or_else(overflow).map(Const::Int),
+                BinaryOp::Mult => a.checked_mul(b).ok_or_else(overflow).map(Const::Int),
+                BinaryOp::Div => a.checked_div(b).ok_or_else(overflow).map(Const::Int),
+                BinaryOp::Mod => a.checked_rem(b).ok_or_else(overflow).map(Const::Int),
+                // Bits shifted out of the value overflow too
+                BinaryOp::LShift => u32::try_from(b)
+                    .ok()
+                    .and_then(|b| a.checked_shl(b).filter(|it| it >> b == a))
+                    .ok_or_else(overflow)
+                    .map(Const::Int),
+                BinaryOp::RShift => u32::try_from(b)
+                    .ok()
+                    .and_then(|b| a.checked_shr(b))
+                    .ok_or_else(overflow)
+                    .map(Const::Int),
+                _ => Err("Unsupported binary operation".to_string()),
+            },
+            (Const::Bool(_), _) | (_, Const::Bool(_)) => {
+                Err("Unsupported operation on booleans".to_string())
+            }
+            // Linear algebra products
+            (Const::Matrix(a), Const::Matrix(b)) if matches!(op, BinaryOp::Mult) => {
+                if a.len() != b.len() {
+                    return Err("Matrix size mismatch".to_string());
+                }
+                Ok(Const::Matrix(matrix_product(&a, &b)))
+            }
+            (Const::Matrix(a), Const::Vector(b)) if matches!(op, BinaryOp::Mult) => {
+                if a.len() != b.len() * b.len() {
+                    return Err("Matrix and vector size mismatch".to_string());
+                }
+                Ok(Const::Vector(matrix_product(&a, &b)))
+            }
+            (Const::Vector(a), Const::Matrix(b)) if matches!(op, BinaryOp::Mult) => {
+                if b.len() != a.len() * a.len() {
+                    return Err("Matrix and vector size mismatch".to_string());
+                }
+                Ok(Const::Vector(
+                    b.chunks(a.len())
+                        .map(|column| column.iter().zip(&a).map(|(m, v)| m * v).sum::<f64>())
+                        .collect(),
+                ))
+            }
+            (Const::Matrix(_), Const::Vector(_)) | (Const::Vector(_), Const::Matrix(_)) => {
+                Err("Unsupported operation between a matrix and a vector".to_string())
+            }
+            // Other operations are component-wise
+            (Const::Matrix(a), Const::Matrix(b)) => {
+                if a.len() != b.len() {
+                    return Err("Matrix size mismatch".to_string());
+                }
+                a.into_iter()
+                    .zip(b)
+                    .map(|(a, b)| float_op(op, a, b))
+                    .collect::<Result<_, _>>()
+                    .map(Const::Matrix)
+            }
+            (Const::Matrix(a), b) => {
+                let b = b.components()?[0];
+                a.into_iter()
+                    .map(|a| float_op(op, a, b))
+                    .collect::<Result<_, _>>()
+                    .map(Const::Matrix)
+            }
+            (a, Const::Matrix(b)) => {
+                let a = a.components()?[0];
+                b.into_iter()
+                    .map(|b| float_op(op, a, b))
+                    .collect::<Result<_, _>>()
+                    .map(Const::Matrix)
+            }
+            (Const::Vector(a), Const::Vector(b)) => {
+                if a.len() != b.len() {
+                    return Err("Vector size mismatch".to_string());
+                }
+                a.into_iter()
+                    .zip(b)
+                    .map(|(a, b)| float_op(op, a, b))
+                    .collect::<Result<_, _>>()
+                    .map(Const::Vector)
+            }
+            // Scalars are applied to every component of the vector
+            (Const::Vector(a), b) => {
+                let b = b.components()?[0];
+                a.into_iter()
+                    .map(|a| float_op(op, a, b))
+                    .collect::<Result<_, _>>()
+                    .map(Const::Vector)
+            }
+            (a, Const::Vector(b)) => {
+                let a = a.components()?[0];
+                b.into_iter()
+                    .map(|b| float_op(op, a, b))
+                    .collect::<Result<_, _>>()
+                    .map(Const::Vector)
+            }
+            (a, b) => float_op(op, a.components()?[0], b.components()?[0]).map(Const::Float),
+        }
+    }
+}
+
+/// Product of a column major square matrix with the columns of another matrix or a vector
+fn matrix_product(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let size = (a.len() as f64).sqrt() as usize;
+    b.chunks(size)
+        .flat_map(|column| {
+            (0..size).map(move |row| {
+                (0..size)
+                    .map(|k| a[k * size + row] * column[k])
+                    .sum::<f64>()
+            })
+        })
+        .collect()
+}
+
+fn overflow() -> String {
+    "Integer overflow".to_string()
+}
+
+fn float_op(op: &BinaryOp, a: f64, b: f64) -> Result<f64, String> {
+    match op {
+        BinaryOp::Add => Ok(a + b),
+        BinaryOp::Sub => Ok(a - b),
+        BinaryOp::Mult => Ok(a * b),
+        BinaryOp::Div => Ok(a / b),
+        _ => Err("Unsupported binary operation".to_string()),
+    }
+}
+
+/// Conversion from a folded constant to the value type of a slider
+trait FromConst: Sized {
+    fn from_const(value: f64) -> Result<Self, String>;
+    fn from_int(value: i64) -> Result<Self, String>;
+}
+
+impl FromConst for f32 {
+    fn from_const(value: f64) -> Result<Self, String> {
+        Ok(value as f32)
+    }
+
+    fn from_int(value: i64) -> Result<Self, String> {
+        Ok(value as f32)
+    }
+}
+
+impl FromConst for i32 {
+    fn from_const(value: f64) -> Result<Self, String> {
+        integer(value, i32::MIN as f64, i32::MAX as f64, "int").map(|it| it as i32)
+    }
+
+    fn from_int(value: i64) -> Result<Self, String> {
+        i32::try_from(value).map_err(|_| format!("{} is out of the range of int", value))
+    }
+}
+
+impl FromConst for u32 {
+    fn from_const(value: f64) -> Result<Self, String> {
+        integer(value, 0.0, u32::MAX as f64, "uint").map(|it| it as u32)
+    }
+
+    fn from_int(value: i64) -> Result<Self, String> {
+        u32::try_from(value).map_err(|_| format!("{} is out of the range of uint", value))
+    }
+}
+
+/// Check that a value is a whole number in the given range, integers are never truncated
+/// or clamped silently
+fn integer(value: f64, min: f64, max: f64, ty: &str) -> Result<f64, String> {
+    if value.fract() != 0.0 {
+        Err(format!("{} is not an integer", value))
+    } else if value < min || value > max {
+        Err(format!("{} is out of the range of {}", value, ty))
+    } else {
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fold an expression with the given macros defined
+    fn eval_with(defines: &[(&str, &str)], expr: &str) -> Result<Const, String> {
+        let extractor = Extractor {
+            defines: defines
+                .iter()
+                .map(|(name, value)| (name.to_string(), Expr::parse(value).unwrap()))
+                .collect(),
+            ..Default::default()
+        };
+        extractor.eval(&Expr::parse(expr).unwrap(), 0)
+    }
+
+    fn eval(expr: &str) -> Result<Const, String> {
+        eval_with(&[], expr)
+    }
+
+    /// Params block with a single field
+    fn extract_field(field: &str) -> Result<ShaderMetadata, PreprocessError> {
+        let source = format!(
+            "layout(params) uniform Params {{\n    {}\n}};\n\nvoid main() {{}}\n",
+            field
+        );
+        extract(&source, "test.frag").map(|(metadata, _)| metadata)
+    }
+
+    fn extract_error(field: &str) -> PreprocessError {
+        match extract_field(field) {
+            Ok(_) => panic!("Expected an error for '{}'", field),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(Const::Int(7)));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(Const::Int(9)));
+        assert_eq!(eval("10 - 4 - 3"), Ok(Const::Int(3)));
+        assert_eq!(eval("-2 * 3 + 10 % 4"), Ok(Const::Int(-4)));
+        assert_eq!(eval("2.0 - 1.0 / 4.0"), Ok(Const::Float(1.75)));
+        assert_eq!(eval("1 << 2 + 1"), Ok(Const::Int(8)));
+    }
+
+    #[test]
+    fn integer_overflow() {
+        assert_eq!(eval("1 << 40"), Ok(Const::Int(1 << 40)));
+        assert_eq!(eval("1 << 63"), Err(overflow()));
+        assert_eq!(eval("2147483647 * 2147483647 * 4"), Err(overflow()));
+        assert_eq!(eval("1 / 0"), Err("Division by zero".to_string()));
+        let e = extract_error("layout(max = 1 << 40) int count;");
+        assert!(e.message.contains("out of the range of int"));
+    }
+
+    #[test]
+    fn define_chains() {
+        let defines = [("A", "2"), ("B", "(A * 3)"), ("C", "B + A")];
+        assert_eq!(eval_with(&defines, "C"), Ok(Const::Int(8)));
+        assert_eq!(eval_with(&defines, "-C / 2"), Ok(Const::Int(-4)));
+        assert!(eval_with(&[("A", "A + 1")], "A")
+            .unwrap_err()
+            .contains("recursive macro"));
+        assert!(eval("UNDEFINED").is_err());
+    }
+
+    #[test]
+    fn matrix_products() {
+        let m = "mat2(1.0, 2.0, 3.0, 4.0)";
+        assert_eq!(
+            eval(&format!("{} * vec2(1.0, 1.0)", m)),
+            Ok(Const::Vector(vec![4.0, 6.0]))
+        );
+        assert_eq!(
+            eval(&format!("vec2(1.0, 1.0) * {}", m)),
+            Ok(Const::Vector(vec![3.0, 7.0]))
+        );
+        // Swapping the columns, the product isn't component-wise nor commutative
+        let swap = "mat2(0.0, 1.0, 1.0, 0.0)";
+        assert_eq!(
+            eval(&format!("{} * {}", m, swap)),
+            Ok(Const::Matrix(vec![3.0, 4.0, 1.0, 2.0]))
+        );
+        assert_eq!(
+            eval(&format!("{} * {}", swap, m)),
+            Ok(Const::Matrix(vec![2.0, 1.0, 4.0, 3.0]))
+        );
+        assert_eq!(
+            eval(&format!("mat2(2.0) * {}", m)),
+            Ok(Const::Matrix(vec![2.0, 4.0, 6.0, 8.0]))
+        );
+        assert!(eval(&format!("{} * vec3(1.0)", m)).is_err());
+    }
+
+    #[test]
+    fn integer_settings() {
+        let metadata =
+            extract_field("layout(min = 2, max = 1048576, init = 4) uint count;").unwrap();
+        assert!(matches!(
+            metadata.params[0].slider,
+            Slider::UInt {
+                min: 2,
+                max: 1_048_576,
+                value: 4,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn integers_are_not_truncated() {
+        let e = extract_error("layout(init = 2.7) int count;");
+        assert_eq!((e.line, e.column), (2, 12));
+        assert!(e.message.contains("not an integer"));
+        let e = extract_error("layout(init = -1) uint count;");
+        assert!(e.message.contains("out of the range of uint"));
+        let e = extract_error("layout(max = 2147483647 + 1) int count;");
+        assert!(e.message.contains("out of the range of int"));
+        let e = extract_error("layout(max = ivec2(1, -1)) uvec2 size;");
+        assert!(e.message.contains("out of the range of uint"));
+    }
+
+    #[test]
+    fn init_within_bounds() {
+        let e = extract_error("layout(min = ivec2(0, 5), max = ivec2(10, 4)) ivec2 size;");
+        assert!(e.message.contains("min greater than its max"));
+        let e = extract_error("layout(min = 0, max = 10, init = 20) int count;");
+        assert!(e.message.contains("outside of its bounds"));
+        let e = extract_error("layout(options = \"a,b\", init = 2) int choice;");
+        assert!(e.message.contains("has 2 options"));
+    }
+
+    #[test]
+    fn step_property() {
+        let (_, source) = extract(
+            "layout(params) uniform Params {\n    layout(max = 20, step = 5) int count;\n};\n\nvoid main() {\n    int steps = count.max / count.step;\n}\n",
+            "test.frag",
+        )
+        .unwrap();
+        assert!(!source.contains("count.max") && !source.contains("count.step"));
+    }
+}
@@ -0,0 +1,745 @@
+//! Extract params from wgsl source.
+//!
+//! Params are the fields of the struct bound with `[[group(1), binding(0)]] var<uniform>`.
+//! Each field can be annotated with a `// @param` comment on the line before it, holding the
+//! settings as `key=value` pairs separated by spaces. Vector values are comma separated and
+//! values with spaces can be quoted. `///` comments before a field are its description.
+//!
+//! ```wgsl
+//! [[block]]
+//! struct Params {
+//!     // @param min=0 max=10 init=1
+//!     value: f32;
+//!     // @param color init=1,0,0
+//!     tint: vec3<f32>;
+//! };
+//!
+//! [[group(1), binding(0)]]
+//! var<uniform> params: Params;
+//! ```
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use mint::{Vector2, Vector3, Vector4};
+
+use crate::input;
+use crate::preprocessor::PreprocessError;
+use crate::shader::{CanvasCoords, KeyBinding, Param, ShaderMetadata, Slider};
+
+/// Marker of the comments holding param settings
+const PARAM_ATTRIBUTE: &str = "// @param";
+
+struct WgslExtractor<'a> {
+    file: &'a str,
+    lines: Vec<&'a str>,
+}
+
+impl<'a> WgslExtractor<'a> {
+    fn error(&self, line: usize, message: impl Into<String>) -> PreprocessError {
+        PreprocessError {
+            file: self.file.to_string(),
+            line: line + 1,
+            column: self.lines[line].len() - self.lines[line].trim_start().len() + 1,
+            message: message.into(),
+        }
+    }
+
+    /// Find the name of the struct used for the params uniform
+    fn params_struct(&self) -> Option<&'a str> {
+        for (i, line) in self.lines.iter().enumerate() {
+            if let Some(pos) = line.find("var<uniform>") {
+                // Attributes can be on the same line or on the line before
+                let attributes = if i > 0 {
+                    format!("{}{}", self.lines[i - 1], &line[..pos])
+                } else {
+                    line[..pos].to_string()
+                };
+                let attributes: String = attributes.split_whitespace().collect();
+                if attributes.contains("group(1)") && attributes.contains("binding(0)") {
+                    return line[pos..]
+                        .split(':')
+                        .nth(1)
+                        .map(|it| it.trim().trim_end_matches(';').trim());
+                }
+            }
+        }
+        None
+    }
+
+    fn extract(&self) -> Result<ShaderMetadata, PreprocessError> {
+        let mut metadata = ShaderMetadata::default();
+
+        let struct_name = match self.params_struct() {
+            Some(name) => name,
+            // No params
+            None => return Ok(metadata),
+        };
+
+        let start = self
+            .lines
+            .iter()
+            .position(|it| {
+                let mut words = it.split_whitespace();
+                words.next() == Some("struct")
+                    && words.next().map(|it| it.trim_end_matches('{')) == Some(struct_name)
+            })
+            .ok_or_else(|| self.error(0, format!("Can't find struct '{}'", struct_name)))?;
+
+        let mut settings: Option<(usize, &str)> = None;
+        let mut doc: Vec<&str> = Vec::new();
+        for i in start + 1..self.lines.len() {
+            let line = self.lines[i].trim();
+            if line.starts_with('}') {
+                break;
+            }
+            // `// @params` or `// @parameter` are other comments
+            if let Some(attribute) = line
+                .strip_prefix(PARAM_ATTRIBUTE)
+                .filter(|it| it.is_empty() || it.starts_with(char::is_whitespace))
+            {
+                settings = Some((i, attribute));
+                continue;
+            }
+            if line.starts_with("///") {
+                doc.push(line.trim_start_matches('/').trim());
+                continue;
+            }
+            // Trailing comments
+            let line = line.find("//").map_or(line, |pos| line[..pos].trim_end());
+            // Attributes before the field, they can be on their own line
+            let (attributes, line) = split_attributes(line);
+            if line.is_empty() {
+                continue;
+            }
+            // The params are written with the default layout
+            if let Some(attribute) = attributes
+                .iter()
+                .find(|it| it.starts_with("size") || it.starts_with("align"))
+            {
+                return Err(self.error(
+                    i,
+                    format!("Attribute '{}' on a param is not supported", attribute),
+                ));
+            }
+            // A field
+            let mut parts = line.trim_end_matches(';').splitn(2, ':');
+            let name = parts.next().unwrap().trim();
+            let ty = parts
+                .next()
+                .ok_or_else(|| self.error(i, "Expected a struct field"))?
+                .trim();
+            let (settings_line, attribute) = settings.take().unwrap_or((i, ""));
+            let mut parsed = self.parse_settings(settings_line, attribute)?;
+            let group = parsed.remove("group").map(str::to_string);
+            let label = parsed.remove("label").map(str::to_string);
+            let mut param = Param::new(self.create_slider(i, settings_line, name, ty, parsed)?);
+            param.group = group;
+            param.label = label;
+            if !doc.is_empty() {
+                param.description = Some(doc.join("\n"));
+                doc.clear();
+            }
+            metadata
+                .add_param(param)
+                .map_err(|e| self.error(settings_line, e))?;
+        }
+
+        Ok(metadata)
+    }
+
+    /// Parse `key=value` pairs, a key without value is a flag. Values can be quoted to hold spaces.
+    fn parse_settings(
+        &self,
+        line: usize,
+        settings: &'a str,
+    ) -> Result<HashMap<&'a str, &'a str>, PreprocessError> {
+        let mut map = HashMap::new();
+        let mut rest = settings.trim_start();
+        while !rest.is_empty() {
+            let end = rest
+                .find(|c: char| c == '=' || c.is_whitespace())
+                .unwrap_or(rest.len());
+            let key = &rest[..end];
+            if key.is_empty() {
+                return Err(self.error(line, "Expected a setting name"));
+            }
+            rest = &rest[end..];
+            let value = match rest.strip_prefix('=') {
+                Some(value) => match value.strip_prefix('"') {
+                    Some(quoted) => {
+                        let end = quoted.find('"').ok_or_else(|| {
+                            self.error(line, format!("Unterminated string for setting '{}'", key))
+                        })?;
+                        rest = &quoted[end + 1..];
+                        &quoted[..end]
+                    }
+                    None => {
+                        let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                        rest = &value[end..];
+                        &value[..end]
+                    }
+                },
+                None => "",
+            };
+            if map.insert(key, value).is_some() {
+                return Err(self.error(line, format!("Duplicate setting '{}'", key)));
+            }
+            rest = rest.trim_start();
+        }
+        Ok(map)
+    }
+
+    /// Get a setting as a number
+    fn number<T: FromStr>(
+        &self,
+        line: usize,
+        settings: &HashMap<&str, &str>,
+        key: &str,
+        default: T,
+    ) -> Result<T, PreprocessError> {
+        match settings.get(key) {
+            Some(value) => value.parse().map_err(|_| {
+                self.error(
+                    line,
+                    format!("Setting '{}' expects a number, got '{}'", key, value),
+                )
+            }),
+            None => Ok(default),
+        }
+    }
+
+    /// Get a setting as comma separated components, a single value is splat
+    fn vector<T: FromStr + Copy>(
+        &self,
+        line: usize,
+        settings: &HashMap<&str, &str>,
+        key: &str,
+        size: usize,
+        default: T,
+    ) -> Result<Vec<T>, PreprocessError> {
+        match settings.get(key) {
+            Some(value) => {
+                let values = value
+                    .split(',')
+                    .map(|it| it.trim().parse())
+                    .collect::<Result<Vec<T>, _>>()
+                    .map_err(|_| self.error(line, format!("Setting '{}' expects numbers", key)))?;
+                match values.len() {
+                    1 => Ok(vec![values[0]; size]),
+                    n if n == size => Ok(values),
+                    _ => Err(self.error(
+                        line,
+                        format!("Setting '{}' expects {} components", key, size),
+                    )),
+                }
+            }
+            None => Ok(vec![default; size]),
+        }
+    }
+
+    fn create_slider(
+        &self,
+        line: usize,
+        settings_line: usize,
+        name: &str,
+        ty: &str,
+        settings: HashMap<&str, &str>,
+    ) -> Result<Slider, PreprocessError> {
+        // Array stride attributes, like [[stride(16)]], don't change the params
+        let ty = ty.rsplit("]]").next().unwrap().trim();
+        if let Some(inner) = ty
+            .strip_prefix("array<")
+            .and_then(|it| it.strip_suffix('>'))
+        {
+            let mut parts = inner.rsplitn(2, ',');
+            let len = parts.next().unwrap().trim();
+            let element = parts
+                .next()
+                .ok_or_else(|| self.error(line, "Arrays in params must be sized"))?
+                .trim();
+            let len: usize = len
+                .trim_end_matches('u')
+                .parse()
+                .map_err(|_| self.error(line, format!("Invalid array size '{}'", len)))?;
+            // Every item gets the settings of the array
+            let items = (0..len)
+                .map(|i| {
+                    self.create_slider(
+                        line,
+                        settings_line,
+                        &format!("{}[{}]", name, i),
+                        element,
+                        settings.clone(),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(Slider::Array {
+                name: name.to_string(),
+                items,
+            });
+        }
+
+        let name = name.to_string();
+        let l = settings_line;
+        // Uniform buffers can't hold bools, a u32 stands in for them
+        if settings.contains_key("checkbox") && ty != "u32" {
+            return Err(self.error(
+                l,
+                format!(
+                    "Setting 'checkbox' on param '{}' needs a u32, bools can't be in uniform buffers",
+                    name
+                ),
+            ));
+        }
+        let allowed: &[&str] = match ty {
+            "f32" => &["min", "max", "step", "log", "init"],
+            "u32" if settings.contains_key("checkbox") => &["init", "checkbox", "key", "hold"],
+            "u32" => &["min", "max", "step", "init"],
+            "i32" => &["min", "max", "step", "init", "options"],
+            "vec2<f32>" => &["init", "min", "max", "speed", "xy", "canvas"],
+            "vec3<f32>" | "vec4<f32>" if settings.contains_key("color") => &["init", "color"],
+            "vec3<f32>" if settings.contains_key("direction") => &["init", "direction"],
+            "vec3<f32>" if settings.contains_key("euler") => &["init", "euler"],
+            "vec3<f32>" | "vec4<f32>" => &["init", "min", "max", "speed"],
+            "vec2<i32>" | "vec3<i32>" | "vec2<u32>" | "vec3<u32>" => {
+                &["init", "min", "max", "step"]
+            }
+            _ => &["init"],
+        };
+        if let Some(key) = settings.keys().find(|it| !allowed.contains(*it)) {
+            return Err(self.error(
+                l,
+                format!("Unsupported setting '{}' on param '{}'", key, name),
+            ));
+        }
+
+        Ok(match ty {
+            "f32" => {
+                let init = self.number(l, &settings, "init", 0.0)?;
+                Slider::Float {
+                    name,
+                    min: self.number(l, &settings, "min", 0.0)?,
+                    max: self.number(l, &settings, "max", 1.0)?,
+                    step: self.number(l, &settings, "step", 0.0f32)?.max(0.0),
+                    log: settings.contains_key("log"),
+                    value: init,
+                    default: init,
+                }
+            }
+            "i32" => {
+                if let Some(options) = settings.get("options") {
+                    let options: Vec<String> = options
+                        .split(',')
+                        .map(|it| it.trim().to_string())
+                        .filter(|it| !it.is_empty())
+                        .collect();
+                    let init = match settings.get("init") {
+                        Some(init) => match options.iter().position(|it| it == init) {
+                            Some(index) => index as i32,
+                            None => self.number(l, &settings, "init", 0)?,
+                        },
+                        None => 0,
+                    };
+                    Slider::Enum {
+                        name,
+                        options,
+                        value: init,
+                        default: init,
+                    }
+                } else {
+                    let init = self.number(l, &settings, "init", 0)?;
+                    Slider::Int {
+                        name,
+                        min: self.number(l, &settings, "min", 0)?,
+                        max: self.number(l, &settings, "max", 100)?,
+                        step: self.number(l, &settings, "step", 1i32)?.max(1),
+                        value: init,
+                        default: init,
+                    }
+                }
+            }
+            "u32" => {
+                if settings.contains_key("checkbox") {
+                    let init = self.number(l, &settings, "init", 0u32)?.min(1);
+                    let key = match settings.get("key") {
+                        Some(key) => Some(KeyBinding {
+                            key: input::key_from_name(key)
+                                .ok_or_else(|| self.error(l, format!("Unknown key '{}'", key)))?,
+                            hold: settings.contains_key("hold"),
+                        }),
+                        None => None,
+                    };
+                    Slider::Bool {
+                        name,
+                        key,
+                        value: init,
+                        default: init,
+                    }
+                } else {
+                    let init = self.number(l, &settings, "init", 0)?;
+                    Slider::UInt {
+                        name,
+                        min: self.number(l, &settings, "min", 0)?,
+                        max: self.number(l, &settings, "max", 100)?,
+                        step: self.number(l, &settings, "step", 1u32)?.max(1),
+                        value: init,
+                        default: init,
+                    }
+                }
+            }
+            "vec2<f32>" => {
+                let init = self.vector(l, &settings, "init", 2, 0.0)?;
+                let init = Vector2::from([init[0], init[1]]);
+                let v = self.vector(l, &settings, "min", 2, f32::NEG_INFINITY)?;
+                let min = Vector2::from([v[0], v[1]]);
+                let v = self.vector(l, &settings, "max", 2, f32::INFINITY)?;
+                let max = Vector2::from([v[0], v[1]]);
+                let v = self.vector(l, &settings, "speed", 2, 0.01)?;
+                let speed = Vector2::from([v[0], v[1]]);
+                let canvas = match settings.get("canvas") {
+                    Some(&"") | Some(&"pixels") => Some(CanvasCoords::Pixels),
+                    Some(&"normalized") => Some(CanvasCoords::Normalized),
+                    Some(_) => {
+                        return Err(self.error(l, "Setting 'canvas' expects pixels or normalized"))
+                    }
+                    None => None,
+                };
+                if settings.contains_key("xy") {
+                    Slider::pad(name, min, max, speed, canvas, init)
+                } else if canvas.is_some() {
+                    return Err(self.error(l, "Setting 'canvas' needs the 'xy' setting"));
+                } else {
+                    Slider::Vec2 {
+                        name,
+                        min,
+                        max,
+                        speed,
+                        value: init,
+                        default: init,
+                    }
+                }
+            }
+            "vec3<f32>" => {
+                let init = self.vector(l, &settings, "init", 3, 0.0)?;
+                let init = Vector3::from([init[0], init[1], init[2]]);
+                if settings.contains_key("color") {
+                    Slider::Color {
+                        name,
+                        value: init,
+                        default: init,
+                    }
+                } else if settings.contains_key("direction") {
+                    Slider::direction(name, init)
+                } else if settings.contains_key("euler") {
+                    Slider::Euler {
+                        name,
+                        value: init,
+                        default: init,
+                    }
+                } else {
+                    let v = self.vector(l, &settings, "min", 3, f32::NEG_INFINITY)?;
+                    let min = Vector3::from([v[0], v[1], v[2]]);
+                    let v = self.vector(l, &settings, "max", 3, f32::INFINITY)?;
+                    let max = Vector3::from([v[0], v[1], v[2]]);
+                    let v = self.vector(l, &settings, "speed", 3, 0.01)?;
+                    let speed = Vector3::from([v[0], v[1], v[2]]);
+                    Slider::Vec3 {
+                        name,
+                        min,
+                        max,
+                        speed,
+                        value: init,
+                        default: init,
+                    }
+                }
+            }
+            "vec4<f32>" => {
+                let init = self.vector(l, &settings, "init", 4, 0.0)?;
+                let init = Vector4::from([init[0], init[1], init[2], init[3]]);
+                if settings.contains_key("color") {
+                    Slider::ColorAlpha {
+                        name,
+                        value: init,
+                        default: init,
+                    }
+                } else {
+                    let v = self.vector(l, &settings, "min", 4, f32::NEG_INFINITY)?;
+                    let min = Vector4::from([v[0], v[1], v[2], v[3]]);
+                    let v = self.vector(l, &settings, "max", 4, f32::INFINITY)?;
+                    let max = Vector4::from([v[0], v[1], v[2], v[3]]);
+                    let v = self.vector(l, &settings, "speed", 4, 0.01)?;
+                    let speed = Vector4::from([v[0], v[1], v[2], v[3]]);
+                    Slider::Vec4 {
+                        name,
+                        min,
+                        max,
+                        speed,
+                        value: init,
+                        default: init,
+                    }
+                }
+            }
+            "vec2<i32>" => {
+                let init = self.vector(l, &settings, "init", 2, 0)?;
+                let init = Vector2::from([init[0], init[1]]);
+                let v = self.vector(l, &settings, "min", 2, 0)?;
+                let min = Vector2::from([v[0], v[1]]);
+                let v = self.vector(l, &settings, "max", 2, 100)?;
+                let max = Vector2::from([v[0], v[1]]);
+                Slider::IVec2 {
+                    name,
+                    min,
+                    max,
+                    step: self.number(l, &settings, "step", 1i32)?.max(1),
+                    value: init,
+                    default: init,
+                }
+            }
+            "vec3<i32>" => {
+                let init = self.vector(l, &settings, "init", 3, 0)?;
+                let init = Vector3::from([init[0], init[1], init[2]]);
+                let v = self.vector(l, &settings, "min", 3, 0)?;
+                let min = Vector3::from([v[0], v[1], v[2]]);
+                let v = self.vector(l, &settings, "max", 3, 100)?;
+                let max = Vector3::from([v[0], v[1], v[2]]);
+                Slider::IVec3 {
+                    name,
+                    min,
+                    max,
+                    step: self.number(l, &settings, "step", 1i32)?.max(1),
+                    value: init,
+                    default: init,
+                }
+            }
+            "vec2<u32>" => {
+                let init = self.vector(l, &settings, "init", 2, 0)?;
+                let init = Vector2::from([init[0], init[1]]);
+                let v = self.vector(l, &settings, "min", 2, 0)?;
+                let min = Vector2::from([v[0], v[1]]);
+                let v = self.vector(l, &settings, "max", 2, 100)?;
+                let max = Vector2::from([v[0], v[1]]);
+                Slider::UVec2 {
+                    name,
+                    min,
+                    max,
+                    step: self.number(l, &settings, "step", 1u32)?.max(1),
+                    value: init,
+                    default: init,
+                }
+            }
+            "vec3<u32>" => {
+                let init = self.vector(l, &settings, "init", 3, 0)?;
+                let init = Vector3::from([init[0], init[1], init[2]]);
+                let v = self.vector(l, &settings, "min", 3, 0)?;
+                let min = Vector3::from([v[0], v[1], v[2]]);
+                let v = self.vector(l, &settings, "max", 3, 100)?;
+                let max = Vector3::from([v[0], v[1], v[2]]);
+                Slider::UVec3 {
+                    name,
+                    min,
+                    max,
+                    step: self.number(l, &settings, "step", 1u32)?.max(1),
+                    value: init,
+                    default: init,
+                }
+            }
+            other => {
+                return Err(self.error(
+                    line,
+                    format!("Unsupported type '{}' for param '{}'", other, name),
+                ))
+            }
+        })
+    }
+}
+
+/// Split the `[[...]]` attributes at the start of a line from the rest
+fn split_attributes(line: &str) -> (Vec<&str>, &str) {
+    let mut attributes = Vec::new();
+    let mut rest = line;
+    while let Some(inner) = rest.strip_prefix("[[") {
+        match inner.find("]]") {
+            Some(end) => {
+                attributes.extend(inner[..end].split(',').map(str::trim));
+                rest = inner[end + 2..].trim_start();
+            }
+            None => break,
+        }
+    }
+    (attributes, rest)
+}
+
+/// Extract the params from a wgsl source. The source itself is left untouched.
+/// `file` is only used for error reporting.
+pub fn extract(source: &str, file: &str) -> Result<ShaderMetadata, PreprocessError> {
+    WgslExtractor {
+        file,
+        lines: source.lines().collect(),
+    }
+    .extract()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Params struct bound to the params uniform, with the given fields
+    fn source(fields: &str) -> String {
+        format!(
+            "[[block]]\nstruct Params {{\n{}\n}};\n\n[[group(1), binding(0)]]\nvar<uniform> params: Params;\n",
+            fields
+        )
+    }
+
+    fn params(fields: &str) -> Vec<Param> {
+        extract(&source(fields), "test.wgsl").unwrap().params
+    }
+
+    fn error(fields: &str) -> PreprocessError {
+        match extract(&source(fields), "test.wgsl") {
+            Ok(_) => panic!("Expected an error"),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn no_params() {
+        let metadata = extract("fn main() {}", "test.wgsl").unwrap();
+        assert!(metadata.params.is_empty());
+    }
+
+    #[test]
+    fn settings() {
+        let params = params("    // @param min=-1 max=10 step=0.5 init=2\n    value: f32;");
+        match params[0].slider {
+            Slider::Float {
+                min,
+                max,
+                step,
+                value,
+                ..
+            } => assert_eq!((min, max, step, value), (-1.0, 10.0, 0.5, 2.0)),
+            _ => panic!("Expected a float"),
+        }
+    }
+
+    #[test]
+    fn defaults_without_settings() {
+        let params = params("    value: f32;\n    count: i32;");
+        assert!(matches!(
+            params[0].slider,
+            Slider::Float { min, max, value, .. } if min == 0.0 && max == 1.0 && value == 0.0
+        ));
+        assert!(matches!(
+            params[1].slider,
+            Slider::Int {
+                min: 0,
+                max: 100,
+                step: 1,
+                value: 0,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn marker_needs_a_separator() {
+        // Not a settings comment, the field keeps its defaults
+        let comment = params("    // @params are below\n    value: f32;");
+        assert!(matches!(comment[0].slider, Slider::Float { max, .. } if max == 1.0));
+        let empty = params("    // @param\n    value: f32;");
+        assert!(matches!(empty[0].slider, Slider::Float { .. }));
+    }
+
+    #[test]
+    fn label_group_and_description() {
+        let params = params(
+            "    /// Light color\n    // @param color label=\"Sun light\" group=Sky\n    sun: vec3<f32>;",
+        );
+        assert!(matches!(params[0].slider, Slider::Color { .. }));
+        assert_eq!(params[0].label.as_deref(), Some("Sun light"));
+        assert_eq!(params[0].group.as_deref(), Some("Sky"));
+        assert_eq!(params[0].description.as_deref(), Some("Light color"));
+    }
+
+    #[test]
+    fn options() {
+        let params = params("    // @param options=Red,Green,Blue init=Green\n    channel: i32;");
+        match &params[0].slider {
+            Slider::Enum { options, value, .. } => {
+                assert_eq!(options, &["Red", "Green", "Blue"]);
+                assert_eq!(*value, 1);
+            }
+            _ => panic!("Expected an enum"),
+        }
+    }
+
+    #[test]
+    fn checkbox() {
+        let params = params("    // @param checkbox init=1 key=Space hold\n    enabled: u32;");
+        assert!(matches!(
+            params[0].slider,
+            Slider::Bool {
+                value: 1,
+                key: Some(KeyBinding { hold: true, .. }),
+                ..
+            }
+        ));
+        assert!(error("    // @param checkbox\n    enabled: f32;")
+            .message
+            .contains("needs a u32"));
+    }
+
+    #[test]
+    fn vectors() {
+        let params = params("    // @param min=0,-1 max=1 init=0.5,0\n    offset: vec2<f32>;");
+        match params[0].slider {
+            Slider::Vec2 {
+                min, max, value, ..
+            } => {
+                assert_eq!((min.x, min.y), (0.0, -1.0));
+                assert_eq!((max.x, max.y), (1.0, 1.0));
+                assert_eq!((value.x, value.y), (0.5, 0.0));
+            }
+            _ => panic!("Expected a vec2"),
+        }
+        let e = error("    // @param init=1,2,3\n    offset: vec2<f32>;");
+        assert!(e.message.contains("expects 2 components"));
+        let e = error("    // @param min=0,2 max=1\n    offset: vec2<f32>;");
+        assert!(e.message.contains("min greater than its max"));
+    }
+
+    #[test]
+    fn arrays_with_stride() {
+        let params = params("    // @param max=4\n    weights: [[stride(16)]] array<f32, 3>;");
+        match &params[0].slider {
+            Slider::Array { items, .. } => {
+                assert_eq!(items.len(), 3);
+                assert_eq!(items[2].name(), "weights[2]");
+                assert!(matches!(items[0], Slider::Float { max, .. } if max == 4.0));
+            }
+            _ => panic!("Expected an array"),
+        }
+    }
+
+    #[test]
+    fn trailing_comments_and_attributes() {
+        let params = params("    [[align(16)]]\n    // @param init=3\n    count: i32; // Loops");
+        assert!(matches!(params[0].slider, Slider::Int { value: 3, .. }));
+    }
+
+    #[test]
+    fn errors_are_located() {
+        let e = error("    value: f32;\n    // @param min=0 speed=2\n    other: f32;");
+        // Line 1 is the struct declaration
+        assert_eq!((e.line, e.column), (4, 5));
+        assert!(e.message.contains("Unsupported setting 'speed'"));
+    }
+
+    #[test]
+    fn init_out_of_bounds() {
+        let e = error("    // @param min=0 max=10 init=20\n    count: i32;");
+        assert!(e.message.contains("outside of its bounds"));
+    }
+}
@@ -0,0 +1,397 @@
+//! Named sets of param values, saved next to the shader in a toml file.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use mint::Vector2;
+use toml::value::Table;
+use toml::Value;
+
+use crate::lfo::{Lfo, Waveform};
+use crate::shader::GradientStop;
+
+/// A saved param value
+#[derive(Debug, Clone)]
+pub enum ParamValue {
+    /// Float params, see [crate::shader::Slider::components]
+    Components(Vec<f64>),
+    /// Int, uint, bool and enum params, and arrays of them
+    Integers(Vec<i64>),
+    Gradient(Vec<GradientStop>),
+    Curve(Vec<Vector2<f32>>),
+}
+
+impl ParamValue {
+    /// Components of a number value, integers are exact in doubles
+    pub fn components(&self) -> Option<Vec<f64>> {
+        match self {
+            ParamValue::Components(components) => Some(components.clone()),
+            ParamValue::Integers(integers) => Some(integers.iter().map(|it| *it as f64).collect()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Preset {
+    pub name: String,
+    /// Values by param name
+    pub values: HashMap<String, ParamValue>,
+    /// Oscillators of the components by param name
+    pub lfos: HashMap<String, Vec<Option<Lfo>>>,
+}
+
+/// Presets of `shader.frag` are saved in `shader.frag.presets.toml`
+pub fn presets_path(shader: &Path) -> PathBuf {
+    let mut name = shader.file_name().unwrap_or_default().to_os_string();
+    name.push(".presets.toml");
+    shader.with_file_name(name)
+}
+
+/// Read the presets of a shader, none if it has no presets file
+pub fn load(shader: &Path) -> Result<Vec<Preset>> {
+    let path = presets_path(shader);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let root: Value = fs::read_to_string(&path)?
+        .parse()
+        .with_context(|| format!("Invalid presets file {}", path.display()))?;
+
+    let presets = match root.get("preset") {
+        Some(Value::Array(presets)) => presets,
+        Some(_) => return Err(anyhow!("'preset' must be an array of tables")),
+        None => return Ok(Vec::new()),
+    };
+    presets
+        .iter()
+        .map(|preset| {
+            let name = preset
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("Preset without a name"))?
+                .to_string();
+            let mut values = HashMap::new();
+            if let Some(table) = preset.get("values").and_then(Value::as_table) {
+                for (param, value) in table.iter() {
+                    let value = parse_value(value)
+                        .ok_or_else(|| anyhow!("Invalid value for '{}' in '{}'", param, name))?;
+                    values.insert(param.clone(), value);
+                }
+            }
+            let mut lfos = HashMap::new();
+            if let Some(table) = preset.get("lfos").and_then(Value::as_table) {
+                for (param, lfo) in table.iter() {
+                    let lfo = parse_lfos(lfo)
+                        .ok_or_else(|| anyhow!("Invalid lfo for '{}' in '{}'", param, name))?;
+                    lfos.insert(param.clone(), lfo);
+                }
+            }
+            Ok(Preset { name, values, lfos })
+        })
+        .collect()
+}
+
+/// Write the presets of a shader, replacing the whole file
+pub fn save(shader: &Path, presets: &[Preset]) -> Result<()> {
+    let presets = presets
+        .iter()
+        .map(|preset| {
+            let mut table = Table::new();
+            table.insert("name".to_string(), Value::String(preset.name.clone()));
+            table.insert(
+                "values".to_string(),
+                Value::Table(
+                    preset
+                        .values
+                        .iter()
+                        .map(|(param, value)| (param.clone(), write_value(value)))
+                        .collect(),
+                ),
+            );
+            if !preset.lfos.is_empty() {
+                table.insert(
+                    "lfos".to_string(),
+                    Value::Table(
+                        preset
+                            .lfos
+                            .iter()
+                            .map(|(param, lfos)| (param.clone(), write_lfos(lfos)))
+                            .collect(),
+                    ),
+                );
+            }
+            Value::Table(table)
+        })
+        .collect();
+    let mut root = Table::new();
+    root.insert("preset".to_string(), Value::Array(presets));
+
+    fs::write(presets_path(shader), toml::to_string(&Value::Table(root))?)?;
+    Ok(())
+}
+
+/// Numbers are plain arrays, of integers for integer params. Gradients are tagged arrays of
+/// `[position, r, g, b, a]` and curves tagged arrays of `[x, y]`.
+fn write_value(value: &ParamValue) -> Value {
+    let floats = |values: &[f32]| -> Value {
+        Value::Array(values.iter().map(|it| Value::Float(*it as f64)).collect())
+    };
+    let tagged = |tag: &str, value: Value| -> Value {
+        let mut table = Table::new();
+        table.insert(tag.to_string(), value);
+        Value::Table(table)
+    };
+    match value {
+        ParamValue::Components(components) => {
+            Value::Array(components.iter().map(|it| Value::Float(*it)).collect())
+        }
+        ParamValue::Integers(integers) => {
+            Value::Array(integers.iter().map(|it| Value::Integer(*it)).collect())
+        }
+        ParamValue::Gradient(stops) => tagged(
+            "gradient",
+            Value::Array(
+                stops
+                    .iter()
+                    .map(|it| {
+                        let c = it.color;
+                        floats(&[it.position, c.x, c.y, c.z, c.w])
+                    })
+                    .collect(),
+            ),
+        ),
+        ParamValue::Curve(points) => tagged(
+            "curve",
+            Value::Array(points.iter().map(|it| floats(&[it.x, it.y])).collect()),
+        ),
+    }
+}
+
+fn parse_value(value: &Value) -> Option<ParamValue> {
+    let number = |it: &Value| {
+        it.as_float()
+            .or_else(|| it.as_integer().map(|it| it as f64))
+    };
+    let floats = |values: &Value| -> Option<Vec<f32>> {
+        values
+            .as_array()?
+            .iter()
+            .map(|it| number(it).map(|it| it as f32))
+            .collect()
+    };
+    match value {
+        Value::Array(values) if values.iter().all(Value::is_integer) => values
+            .iter()
+            .map(Value::as_integer)
+            .collect::<Option<_>>()
+            .map(ParamValue::Integers),
+        Value::Array(values) => values
+            .iter()
+            .map(number)
+            .collect::<Option<_>>()
+            .map(ParamValue::Components),
+        Value::Table(table) => match table.iter().next()? {
+            (tag, Value::Array(stops)) if tag == "gradient" => stops
+                .iter()
+                .map(|it| match floats(it)?.as_slice() {
+                    [position, r, g, b, a] => Some(GradientStop {
+                        position: *position,
+                        color: [*r, *g, *b, *a].into(),
+                    }),
+                    _ => None,
+                })
+                .collect::<Option<_>>()
+                .map(ParamValue::Gradient),
+            (tag, Value::Array(points)) if tag == "curve" => points
+                .iter()
+                .map(|it| match floats(it)?.as_slice() {
+                    [x, y] => Some(Vector2::from([*x, *y])),
+                    _ => None,
+                })
+                .collect::<Option<_>>()
+                .map(ParamValue::Curve),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A scalar param has a single table, other params an array with empty tables for the
+/// components that aren't modulated
+fn write_lfos(lfos: &[Option<Lfo>]) -> Value {
+    match lfos {
+        [Some(lfo)] => write_lfo(lfo),
+        _ => Value::Array(
+            lfos.iter()
+                .map(|it| {
+                    it.as_ref()
+                        .map_or_else(|| Value::Table(Table::new()), write_lfo)
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn parse_lfos(value: &Value) -> Option<Vec<Option<Lfo>>> {
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Table(table) if table.is_empty() => Some(None),
+                _ => parse_lfo(item).map(Some),
+            })
+            .collect(),
+        _ => Some(vec![Some(parse_lfo(value)?)]),
+    }
+}
+
+fn write_lfo(lfo: &Lfo) -> Value {
+    let mut table = Table::new();
+    table.insert(
+        "waveform".to_string(),
+        Value::String(lfo.waveform.name().to_string()),
+    );
+    for (key, value) in [
+        ("frequency", lfo.frequency),
+        ("phase", lfo.phase),
+        ("amplitude", lfo.amplitude),
+        ("offset", lfo.offset),
+    ]
+    .iter()
+    {
+        table.insert(key.to_string(), Value::Float(*value as f64));
+    }
+    Value::Table(table)
+}
+
+fn parse_lfo(value: &Value) -> Option<Lfo> {
+    let number = |key: &str| -> Option<f32> {
+        let value = value.get(key)?;
+        value
+            .as_float()
+            .or_else(|| value.as_integer().map(|it| it as f64))
+            .map(|it| it as f32)
+    };
+    Some(Lfo {
+        waveform: Waveform::from_name(value.get("waveform")?.as_str()?)?,
+        frequency: number("frequency")?,
+        phase: number("phase")?,
+        amplitude: number("amplitude")?,
+        offset: number("offset")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a value in a toml document and parse it back
+    fn round_trip(value: &ParamValue) -> ParamValue {
+        let mut table = Table::new();
+        table.insert("value".to_string(), write_value(value));
+        let text = toml::to_string(&Value::Table(table)).unwrap();
+        let root: Value = text.parse().unwrap();
+        parse_value(&root["value"]).unwrap()
+    }
+
+    #[test]
+    fn integers_are_exact() {
+        let integers = vec![i64::from(u32::MAX), i64::from(i32::MIN), (1 << 24) + 1];
+        match round_trip(&ParamValue::Integers(integers.clone())) {
+            ParamValue::Integers(parsed) => assert_eq!(parsed, integers),
+            other => panic!("Expected integers, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn whole_floats_stay_floats() {
+        let components = vec![1.0, -2.0, 0.1, 1e10];
+        match round_trip(&ParamValue::Components(components.clone())) {
+            ParamValue::Components(parsed) => assert_eq!(parsed, components),
+            other => panic!("Expected components, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn gradient() {
+        let stops = vec![
+            GradientStop {
+                position: 0.0,
+                color: [1.0, 0.0, 0.0, 1.0].into(),
+            },
+            GradientStop {
+                position: 0.5,
+                color: [0.0, 0.25, 1.0, 0.5].into(),
+            },
+        ];
+        match round_trip(&ParamValue::Gradient(stops.clone())) {
+            ParamValue::Gradient(parsed) => {
+                assert_eq!(format!("{:?}", parsed), format!("{:?}", stops))
+            }
+            other => panic!("Expected a gradient, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn curve() {
+        // Told apart from a gradient by its tag, not by the length of the points
+        let points = vec![Vector2::from([0.0, 0.5]), Vector2::from([1.0, 0.25])];
+        match round_trip(&ParamValue::Curve(points.clone())) {
+            ParamValue::Curve(parsed) => assert_eq!(parsed, points),
+            other => panic!("Expected a curve, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_tag() {
+        let value: Value = "value = { spline = [[0.0, 1.0]] }".parse().unwrap();
+        assert!(parse_value(&value["value"]).is_none());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let shader = std::env::temp_dir().join(format!("nuance-{}.frag", std::process::id()));
+        let preset = Preset {
+            name: "Sunset".to_string(),
+            values: vec![
+                (
+                    "steps".to_string(),
+                    ParamValue::Integers(vec![3_000_000_001]),
+                ),
+                (
+                    "tint".to_string(),
+                    ParamValue::Components(vec![1.0, 0.5, 0.25]),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            lfos: vec![(
+                "tint".to_string(),
+                vec![None, Some(Lfo::new(0.0, 1.0)), None],
+            )]
+            .into_iter()
+            .collect(),
+        };
+        save(&shader, &[preset]).unwrap();
+        let loaded = load(&shader);
+        fs::remove_file(presets_path(&shader)).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].name, "Sunset");
+        assert_eq!(
+            format!("{:?}", loaded[0].values["steps"]),
+            format!("{:?}", ParamValue::Integers(vec![3_000_000_001]))
+        );
+        assert_eq!(
+            format!("{:?}", loaded[0].values["tint"]),
+            format!("{:?}", ParamValue::Components(vec![1.0, 0.5, 0.25]))
+        );
+        let lfos = &loaded[0].lfos["tint"];
+        assert_eq!(lfos.len(), 3);
+        assert!(lfos[0].is_none() && lfos[1].is_some() && lfos[2].is_none());
+    }
+}
@@ -0,0 +1,29 @@
+//! Seeded pseudo random numbers, so explorations can be replayed.
+
+/// SplitMix64 generator
+/// Source : https://prng.di.unimi.it/splitmix64.c
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value between 0 and 1
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform value between -1 and 1
+    pub fn next_signed(&mut self) -> f32 {
+        self.next_f32() * 2.0 - 1.0
+    }
+}
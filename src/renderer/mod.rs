@@ -10,10 +10,22 @@ use puffin::ProfilerScope;
 use wgpu::*;
 use winit::window::Window;
 
-use crate::renderer::shader::ShaderRenderPass;
+use crate::renderer::shader::{ShaderPassDesc, ShaderRenderPass};
+use crate::shader::LUT_SIZE;
 
 mod shader;
 
+/// Data uploaded to the gpu before the shader renders a frame
+pub struct FrameUploads<'a> {
+    /// The params block
+    pub params: &'a [u8],
+    /// Baked gradients and curves, None to keep the ones already uploaded
+    pub luts: Option<&'a [u8]>,
+    /// Key states, see [crate::input::Keyboard]
+    pub keyboard: &'a [u8],
+    pub push_constants: &'a [u8],
+}
+
 pub struct Renderer {
     #[allow(dead_code)]
     instance: Instance,
@@ -237,6 +249,7 @@ impl Renderer {
         shader_source: ShaderSource,
         push_constant_size: u32,
         params_buffer_size: u64,
+        lut_count: u32,
     ) {
         let module = self.device.create_shader_module(&ShaderModuleDescriptor {
             label: Some("nuance fragment shader"),
@@ -248,9 +261,12 @@ impl Renderer {
             &self.vertex_shader,
             &module,
             &self.last_render_tex_bgl,
-            push_constant_size,
-            params_buffer_size,
-            self.format,
+            ShaderPassDesc {
+                push_constants_size: push_constant_size,
+                params_buffer_size,
+                lut_count,
+                format: self.format,
+            },
         ));
         self.shader_module = Some(module);
     }
@@ -259,8 +275,7 @@ impl Renderer {
         &mut self,
         screen_desc: &ScreenDescriptor,
         gui: (&egui::Texture, &[ClippedMesh]),
-        params_buffer: &[u8],
-        push_constants: &[u8],
+        uploads: &FrameUploads,
         should_render: bool,
     ) -> Result<()> {
         puffin::profile_function!();
@@ -281,13 +296,18 @@ impl Renderer {
         mem::drop(_profiler_scope);
 
         if should_render {
-            if let Some(shader_rpass) = self.shader_rpass.as_ref() {
+            if let Some(shader_rpass) = self.shader_rpass.as_mut() {
                 puffin::profile_scope!("shader render pass");
-                shader_rpass.update_buffers(&self.queue, params_buffer);
+                shader_rpass.update_buffers(
+                    &self.queue,
+                    uploads.params,
+                    uploads.luts,
+                    uploads.keyboard,
+                );
                 shader_rpass.execute(
                     &mut encoder,
                     &render_tex_view,
-                    push_constants,
+                    uploads.push_constants,
                     &self.last_render_tex_bg,
                 );
             }
@@ -340,11 +360,11 @@ impl Renderer {
         Ok(())
     }
 
+    /// Render the shader once in a new pass, every lookup texture must be given
     pub fn render_to_buffer(
         &self,
         render_size: Vector2<u32>,
-        params_buffer: &[u8],
-        push_constants: &[u8],
+        uploads: &FrameUploads,
         consume: impl FnOnce(BufferView) -> Result<()>,
     ) -> Result<()> {
         if render_size.x % 64 != 0 {
@@ -376,14 +396,17 @@ impl Renderer {
         };
         let output_buffer = self.device.create_buffer(&output_buffer_desc);
 
-        let shader_rpass = ShaderRenderPass::new(
+        let mut shader_rpass = ShaderRenderPass::new(
             &self.device,
             &self.vertex_shader,
             self.shader_module.as_ref().unwrap(),
             &self.last_render_tex_bgl,
-            push_constants.len() as u32,
-            params_buffer.len() as u64,
-            self.format,
+            ShaderPassDesc {
+                push_constants_size: uploads.push_constants.len() as u32,
+                params_buffer_size: uploads.params.len() as u64,
+                lut_count: (uploads.luts.unwrap_or_default().len() / (LUT_SIZE * 16)) as u32,
+                format: self.format,
+            },
         );
 
         let render_tex_view = render_tex.create_view(&TextureViewDescriptor::default());
@@ -395,11 +418,11 @@ impl Renderer {
                 label: Some("image render"),
             });
 
-        shader_rpass.update_buffers(&self.queue, params_buffer);
+        shader_rpass.update_buffers(&self.queue, uploads.params, uploads.luts, uploads.keyboard);
         shader_rpass.execute(
             &mut encoder,
             &render_tex_view,
-            push_constants,
+            uploads.push_constants,
             &self.last_render_tex_bg,
         );
 
@@ -1,21 +1,47 @@
+use std::num::NonZeroU32;
+
 use wgpu::*;
 
+use crate::input::KEYBOARD_SIZE;
+use crate::shader::LUT_SIZE;
+
 pub(crate) struct ShaderRenderPass {
     params_bind_group: Option<BindGroup>,
     params_buffer: Option<Buffer>,
+    /// Gradients and curves baked by the cpu
+    lut_bind_group: Option<BindGroup>,
+    lut_textures: Vec<Texture>,
+    /// Key states, see [crate::input::Keyboard]
+    keyboard_bind_group: BindGroup,
+    keyboard_texture: Texture,
+    /// Bound to the sets the shader has no use for
+    empty_bind_group: BindGroup,
     pipeline: RenderPipeline,
 }
 
+/// What the pipeline of a shader needs to be created
+pub(crate) struct ShaderPassDesc {
+    pub(crate) push_constants_size: u32,
+    pub(crate) params_buffer_size: u64,
+    /// Number of gradients and curves, each gets a lookup texture
+    pub(crate) lut_count: u32,
+    pub(crate) format: TextureFormat,
+}
+
 impl ShaderRenderPass {
     pub(crate) fn new(
         device: &Device,
         vertex_shader: &ShaderModule,
         shader_source: &ShaderModule,
         last_tex_layout: &BindGroupLayout,
-        push_constants_size: u32,
-        params_buffer_size: u64,
-        format: TextureFormat,
+        desc: ShaderPassDesc,
     ) -> Self {
+        let ShaderPassDesc {
+            push_constants_size,
+            params_buffer_size,
+            lut_count,
+            format,
+        } = desc;
         let bind_group_layout;
         let params_buffer;
         let params_bind_group;
@@ -59,11 +85,139 @@ impl ShaderRenderPass {
             params_bind_group = None;
         }
 
-        let mut layouts = vec![last_tex_layout];
-        if let Some(layout) = &bind_group_layout {
-            layouts.push(layout);
+        let lut_layout;
+        let lut_textures: Vec<Texture>;
+        let lut_bind_group;
+        if lut_count > 0 {
+            // Binding 0 is the sampler, then a texture for each gradient or curve
+            let mut entries = vec![BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStage::FRAGMENT,
+                ty: BindingType::Sampler {
+                    filtering: false,
+                    comparison: false,
+                },
+                count: None,
+            }];
+            entries.extend((1..=lut_count).map(|binding| BindGroupLayoutEntry {
+                binding,
+                visibility: ShaderStage::FRAGMENT,
+                ty: BindingType::Texture {
+                    sample_type: TextureSampleType::Float { filterable: false },
+                    view_dimension: TextureViewDimension::D1,
+                    multisampled: false,
+                },
+                count: None,
+            }));
+            lut_layout = Some(device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: Some("lut bind group layout"),
+                entries: &entries,
+            }));
+
+            lut_textures = (0..lut_count)
+                .map(|_| {
+                    device.create_texture(&TextureDescriptor {
+                        label: Some("lut texture"),
+                        size: Extent3d {
+                            width: LUT_SIZE as u32,
+                            height: 1,
+                            depth_or_array_layers: 1,
+                        },
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: TextureDimension::D1,
+                        format: TextureFormat::Rgba32Float,
+                        usage: TextureUsage::SAMPLED | TextureUsage::COPY_DST,
+                    })
+                })
+                .collect();
+            let views: Vec<TextureView> = lut_textures
+                .iter()
+                .map(|texture| texture.create_view(&TextureViewDescriptor::default()))
+                .collect();
+            let sampler = device.create_sampler(&SamplerDescriptor {
+                label: Some("lut sampler"),
+                address_mode_u: AddressMode::ClampToEdge,
+                mag_filter: FilterMode::Nearest,
+                min_filter: FilterMode::Nearest,
+                ..Default::default()
+            });
+
+            let mut entries = vec![BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::Sampler(&sampler),
+            }];
+            entries.extend(views.iter().zip(1..).map(|(view, binding)| BindGroupEntry {
+                binding,
+                resource: BindingResource::TextureView(view),
+            }));
+            lut_bind_group = Some(device.create_bind_group(&BindGroupDescriptor {
+                label: Some("lut bind group"),
+                layout: lut_layout.as_ref().unwrap(),
+                entries: &entries,
+            }));
+        } else {
+            lut_layout = None;
+            lut_textures = Vec::new();
+            lut_bind_group = None;
         }
 
+        // The keyboard texture is always bound to set 3, sampled with the last frame sampler
+        let keyboard_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("keyboard bind group layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStage::FRAGMENT,
+                ty: BindingType::Texture {
+                    sample_type: TextureSampleType::Float { filterable: false },
+                    view_dimension: TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+        });
+        let keyboard_texture = device.create_texture(&TextureDescriptor {
+            label: Some("keyboard texture"),
+            size: Extent3d {
+                width: KEYBOARD_SIZE as u32,
+                height: 3,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::R8Unorm,
+            usage: TextureUsage::SAMPLED | TextureUsage::COPY_DST,
+        });
+        let keyboard_bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("keyboard bind group"),
+            layout: &keyboard_layout,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(
+                    &keyboard_texture.create_view(&TextureViewDescriptor::default()),
+                ),
+            }],
+        });
+
+        // Sets 1 and 2 can't be left out when there is a set after them
+        let empty_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("empty bind group layout"),
+            entries: &[],
+        });
+        let empty_bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("empty bind group"),
+            layout: &empty_layout,
+            entries: &[],
+        });
+
+        let layouts = [
+            last_tex_layout,
+            bind_group_layout.as_ref().unwrap_or(&empty_layout),
+            lut_layout.as_ref().unwrap_or(&empty_layout),
+            &keyboard_layout,
+        ];
+
         let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: Some("nuance shader pipeline layout"),
             bind_group_layouts: &layouts,
@@ -111,15 +265,74 @@ impl ShaderRenderPass {
         Self {
             params_bind_group,
             params_buffer,
+            lut_bind_group,
+            lut_textures,
+            keyboard_bind_group,
+            keyboard_texture,
+            empty_bind_group,
             pipeline,
         }
     }
 
-    pub(crate) fn update_buffers(&self, queue: &Queue, params_buffer: &[u8]) {
+    pub(crate) fn update_buffers(
+        &mut self,
+        queue: &Queue,
+        params_buffer: &[u8],
+        lut_buffer: Option<&[u8]>,
+        keyboard: &[u8],
+    ) {
         if let Some(buffer) = &self.params_buffer {
             // Update the params buffer on the gpu side
             queue.write_buffer(buffer, 0, params_buffer);
         }
+
+        // Gradients and curves are only given when edited
+        if let Some(lut_buffer) = lut_buffer {
+            let texture_size = LUT_SIZE * 16;
+            for (texture, data) in self
+                .lut_textures
+                .iter()
+                .zip(lut_buffer.chunks_exact(texture_size))
+            {
+                queue.write_texture(
+                    ImageCopyTexture {
+                        texture,
+                        mip_level: 0,
+                        origin: Origin3d::ZERO,
+                    },
+                    data,
+                    ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: NonZeroU32::new(texture_size as u32),
+                        rows_per_image: None,
+                    },
+                    Extent3d {
+                        width: LUT_SIZE as u32,
+                        height: 1,
+                        depth_or_array_layers: 1,
+                    },
+                );
+            }
+        }
+
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: &self.keyboard_texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+            },
+            keyboard,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(KEYBOARD_SIZE as u32),
+                rows_per_image: None,
+            },
+            Extent3d {
+                width: KEYBOARD_SIZE as u32,
+                height: 3,
+                depth_or_array_layers: 1,
+            },
+        );
     }
 
     pub(crate) fn execute(
@@ -144,9 +357,21 @@ impl ShaderRenderPass {
             depth_stencil_attachment: None,
         });
         rpass.set_bind_group(0, last_tex, &[]);
-        if let Some(bind_group) = &self.params_bind_group {
-            rpass.set_bind_group(1, bind_group, &[]);
-        }
+        rpass.set_bind_group(
+            1,
+            self.params_bind_group
+                .as_ref()
+                .unwrap_or(&self.empty_bind_group),
+            &[],
+        );
+        rpass.set_bind_group(
+            2,
+            self.lut_bind_group
+                .as_ref()
+                .unwrap_or(&self.empty_bind_group),
+            &[],
+        );
+        rpass.set_bind_group(3, &self.keyboard_bind_group, &[]);
         rpass.set_pipeline(&self.pipeline);
         // Push constants mapped to uniform block
         rpass.set_push_constants(ShaderStage::FRAGMENT, 0, push_constants);
@@ -1,45 +1,239 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::mem;
 use std::path::PathBuf;
 
 use crevice::std140;
-use mint::{Vector2, Vector3};
+use mint::{ColumnMatrix2, ColumnMatrix3, ColumnMatrix4, Vector2, Vector3, Vector4};
+use winit::event::VirtualKeyCode;
+
+use crate::lfo::Lfo;
+use crate::presets::{ParamValue, Preset};
+use crate::random::Rng;
+use crate::timeline::{self, Interpolation, Keyframe};
 
 pub enum Slider {
     Float {
         name: String,
         min: f32,
         max: f32,
+        /// 0 for a continuous value
+        step: f32,
+        /// Drag on a logarithmic scale
+        log: bool,
         value: f32,
         default: f32,
     },
+    Int {
+        name: String,
+        min: i32,
+        max: i32,
+        step: i32,
+        value: i32,
+        default: i32,
+    },
+    UInt {
+        name: String,
+        min: u32,
+        max: u32,
+        step: u32,
+        value: u32,
+        default: u32,
+    },
     Vec2 {
         name: String,
+        min: Vector2<f32>,
+        max: Vector2<f32>,
+        speed: Vector2<f32>,
+        value: Vector2<f32>,
+        default: Vector2<f32>,
+    },
+    /// A vec2 param edited on a 2d pad
+    Pad {
+        name: String,
+        min: Vector2<f32>,
+        max: Vector2<f32>,
+        speed: Vector2<f32>,
+        /// Whether the value can be set by dragging on the canvas
+        canvas: Option<CanvasCoords>,
         value: Vector2<f32>,
         default: Vector2<f32>,
     },
     Vec3 {
+        name: String,
+        min: Vector3<f32>,
+        max: Vector3<f32>,
+        speed: Vector3<f32>,
+        value: Vector3<f32>,
+        default: Vector3<f32>,
+    },
+    /// A normalized vec3 param
+    Direction {
         name: String,
         value: Vector3<f32>,
         default: Vector3<f32>,
     },
+    /// A vec3 param holding rotation angles in radians
+    Euler {
+        name: String,
+        value: Vector3<f32>,
+        default: Vector3<f32>,
+    },
+    Vec4 {
+        name: String,
+        min: Vector4<f32>,
+        max: Vector4<f32>,
+        speed: Vector4<f32>,
+        value: Vector4<f32>,
+        default: Vector4<f32>,
+    },
     Color {
         name: String,
         value: Vector3<f32>,
         default: Vector3<f32>,
     },
+    ColorAlpha {
+        name: String,
+        value: Vector4<f32>,
+        default: Vector4<f32>,
+    },
+    IVec2 {
+        name: String,
+        min: Vector2<i32>,
+        max: Vector2<i32>,
+        step: i32,
+        value: Vector2<i32>,
+        default: Vector2<i32>,
+    },
+    IVec3 {
+        name: String,
+        min: Vector3<i32>,
+        max: Vector3<i32>,
+        step: i32,
+        value: Vector3<i32>,
+        default: Vector3<i32>,
+    },
+    UVec2 {
+        name: String,
+        min: Vector2<u32>,
+        max: Vector2<u32>,
+        step: u32,
+        value: Vector2<u32>,
+        default: Vector2<u32>,
+    },
+    UVec3 {
+        name: String,
+        min: Vector3<u32>,
+        max: Vector3<u32>,
+        step: u32,
+        value: Vector3<u32>,
+        default: Vector3<u32>,
+    },
+    Mat2 {
+        name: String,
+        value: ColumnMatrix2<f32>,
+        default: ColumnMatrix2<f32>,
+    },
+    Mat3 {
+        name: String,
+        value: ColumnMatrix3<f32>,
+        default: ColumnMatrix3<f32>,
+    },
+    Mat4 {
+        name: String,
+        value: ColumnMatrix4<f32>,
+        default: ColumnMatrix4<f32>,
+    },
     Bool {
         name: String,
+        /// Key toggling or holding the value
+        key: Option<KeyBinding>,
         value: u32,
         default: u32,
     },
+    /// An int param with named values
+    Enum {
+        name: String,
+        options: Vec<String>,
+        value: i32,
+        default: i32,
+    },
+    /// A fixed size array, each item has its own slider
+    Array { name: String, items: Vec<Slider> },
+    /// A color gradient baked into a lookup texture
+    Gradient {
+        name: String,
+        /// Whether the shader samples a vec4
+        alpha: bool,
+        /// Stops sorted by position
+        value: Vec<GradientStop>,
+        default: Vec<GradientStop>,
+    },
+    /// A spline curve baked into a lookup texture
+    Curve {
+        name: String,
+        min: f32,
+        max: f32,
+        /// Control points sorted by x, x is between 0 and 1
+        value: Vec<Vector2<f32>>,
+        default: Vec<Vector2<f32>>,
+    },
+}
+
+/// Number of texels in the lookup texture of a gradient or curve
+pub const LUT_SIZE: usize = 256;
+
+#[derive(Debug, Copy, Clone)]
+pub struct GradientStop {
+    /// Between 0 and 1
+    pub position: f32,
+    pub color: Vector4<f32>,
+}
+
+/// Keyboard control of a bool param
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct KeyBinding {
+    pub key: VirtualKeyCode,
+    /// The value is true while the key is held, it is toggled on each press otherwise
+    pub hold: bool,
+}
+
+/// Coordinates a param receives when dragging on the canvas
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CanvasCoords {
+    /// Same as fragCoord
+    Pixels,
+    /// Same as fragCoordNorm
+    Normalized,
+}
+
+macro_rules! name_impl {
+    ($enum:ident, $($item:ident )*) => (
+        impl $enum {
+            /// Name of the param in the shader source
+            pub fn name(&self) -> &str {
+                match self {
+                    $($enum::$item { name, .. } => name,)*
+                }
+            }
+        }
+    )
 }
 
+name_impl!(Slider, Float Int UInt Vec2 Pad Vec3 Direction Euler Vec4 Color ColorAlpha IVec2 IVec3 UVec2 UVec3 Mat2 Mat3 Mat4 Bool Enum Array Gradient Curve);
+
 macro_rules! reset_impl {
-    ($enum:ident, $($item: ident )*) => (
+    ($enum:ident, $($item: ident )*; $($array:ident )*) => (
         impl $enum {
             pub fn reset(&mut self) {
                 match self {
                     $($enum::$item { value, default, .. } => {
-                        *value = *default;
+                        *value = default.clone();
+                    })*
+                    $($enum::$array { items, .. } => {
+                        for item in items.iter_mut() {
+                            item.reset();
+                        }
                     })*
                 }
             }
@@ -47,32 +241,1111 @@ macro_rules! reset_impl {
     )
 }
 
-reset_impl!(Slider, Float Vec2 Vec3 Color Bool);
+reset_impl!(Slider, Float Int UInt Vec2 Pad Vec3 Direction Euler Vec4 Color ColorAlpha IVec2 IVec3 UVec2 UVec3 Mat2 Mat3 Mat4 Bool Enum Gradient Curve; Array);
 
 macro_rules! write_impl {
-    ($align:ident, $enum:ident, $($item:ident )*) => {
+    ($align:ident, $enum:ident, $($item:ident )*; $($matrix:ident )*; $($array:ident )*; $($lut:ident )*) => {
         impl $enum {
             pub fn write<W: std::io::Write>(&self, writer: &mut crevice::$align::Writer<W>) {
                 match self {
                     $($enum::$item { value, .. } => {
                         writer.write(value).unwrap();
                     })*
+                    $($enum::$matrix { value, .. } => {
+                        for column in value.padded_columns().iter() {
+                            writer.write(column).unwrap();
+                        }
+                    })*
+                    // Array items are aligned to 16 bytes, even scalars
+                    $($enum::$array { items, .. } => {
+                        for item in items.iter() {
+                            pad(writer, 16);
+                            item.write(writer);
+                        }
+                        pad(writer, 16);
+                    })*
+                    // Baked into textures instead
+                    $($enum::$lut { .. } => {})*
                 }
             }
         }
     };
 }
 
-write_impl!(std140, Slider, Float Vec2 Vec3 Color Bool);
+write_impl!(std140, Slider, Float Int UInt Vec2 Pad Vec3 Direction Euler Vec4 Color ColorAlpha IVec2 IVec3 UVec2 UVec3 Bool Enum; Mat2 Mat3 Mat4; Array; Gradient Curve);
+
+impl Slider {
+    /// Sample the gradient or the curve for a lookup texture
+    pub fn bake(&self) -> Option<Vec<[f32; 4]>> {
+        let texels = (0..LUT_SIZE).map(|i| i as f32 / (LUT_SIZE - 1) as f32);
+        match self {
+            Slider::Gradient { value, .. } => {
+                Some(texels.map(|t| sample_gradient(value, t).into()).collect())
+            }
+            Slider::Curve {
+                min, max, value, ..
+            } => Some(
+                texels
+                    .map(|t| {
+                        let y = sample_curve(value, t).max(*min).min(*max);
+                        [y, y, y, y]
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// The value as a list of floats, so any param can be interpolated the same way.
+    /// Doubles hold every int and uint exactly. None for gradients and curves.
+    pub fn components(&self) -> Option<Vec<f64>> {
+        let floats = |values: &[f32]| -> Vec<f64> { values.iter().map(|it| *it as f64).collect() };
+        Some(match self {
+            Slider::Float { value, .. } => vec![*value as f64],
+            Slider::Int { value, .. } | Slider::Enum { value, .. } => vec![*value as f64],
+            Slider::UInt { value, .. } | Slider::Bool { value, .. } => vec![*value as f64],
+            Slider::Vec2 { value, .. } | Slider::Pad { value, .. } => floats(value.as_ref()),
+            Slider::Vec3 { value, .. }
+            | Slider::Direction { value, .. }
+            | Slider::Euler { value, .. }
+            | Slider::Color { value, .. } => floats(value.as_ref()),
+            Slider::Vec4 { value, .. } | Slider::ColorAlpha { value, .. } => floats(value.as_ref()),
+            Slider::IVec2 { value, .. } => vec![value.x as f64, value.y as f64],
+            Slider::IVec3 { value, .. } => vec![value.x as f64, value.y as f64, value.z as f64],
+            Slider::UVec2 { value, .. } => vec![value.x as f64, value.y as f64],
+            Slider::UVec3 { value, .. } => vec![value.x as f64, value.y as f64, value.z as f64],
+            Slider::Mat2 { value, .. } => floats(&<[[f32; 2]; 2]>::from(*value).concat()),
+            Slider::Mat3 { value, .. } => floats(&<[[f32; 3]; 3]>::from(*value).concat()),
+            Slider::Mat4 { value, .. } => floats(&<[[f32; 4]; 4]>::from(*value).concat()),
+            Slider::Array { items, .. } => items
+                .iter()
+                .map(Slider::components)
+                .collect::<Option<Vec<_>>>()?
+                .concat(),
+            Slider::Gradient { .. } | Slider::Curve { .. } => return None,
+        })
+    }
+
+    /// Set the value from a list of floats as given by [Slider::components].
+    /// Integers are rounded, values are snapped to their step and kept in their bounds.
+    pub fn set_components(&mut self, c: &[f64]) {
+        // Float params only need single precision
+        let f: Vec<f32> = c.iter().map(|it| *it as f32).collect();
+        match self {
+            Slider::Float {
+                min,
+                max,
+                step,
+                value,
+                ..
+            } => {
+                let snapped = snap(c[0], f64::from(*min), f64::from(*max), f64::from(*step));
+                *value = (snapped as f32).max(*min).min(*max)
+            }
+            Slider::Int {
+                min,
+                max,
+                step,
+                value,
+                ..
+            } => *value = snap_int(c[0], *min, *max, *step),
+            Slider::UInt {
+                min,
+                max,
+                step,
+                value,
+                ..
+            } => *value = snap_int(c[0], *min, *max, *step),
+            Slider::Enum { options, value, .. } => {
+                *value = (f[0].round() as i32).max(0).min(options.len() as i32 - 1)
+            }
+            Slider::Bool { value, .. } => *value = if f[0] >= 0.5 { 1 } else { 0 },
+            Slider::Vec2 {
+                min, max, value, ..
+            }
+            | Slider::Pad {
+                min, max, value, ..
+            } => {
+                value.x = f[0].max(min.x).min(max.x);
+                value.y = f[1].max(min.y).min(max.y);
+            }
+            Slider::Vec3 {
+                min, max, value, ..
+            } => {
+                value.x = f[0].max(min.x).min(max.x);
+                value.y = f[1].max(min.y).min(max.y);
+                value.z = f[2].max(min.z).min(max.z);
+            }
+            Slider::Direction { value, .. } => {
+                let len = (f[0] * f[0] + f[1] * f[1] + f[2] * f[2]).sqrt();
+                if len > 0.0 {
+                    *value = Vector3::from([f[0] / len, f[1] / len, f[2] / len]);
+                }
+            }
+            Slider::Euler { value, .. } => *value = Vector3::from([f[0], f[1], f[2]]),
+            Slider::Color { value, .. } => {
+                *value = Vector3::from([f[0], f[1], f[2]]);
+            }
+            Slider::Vec4 {
+                min, max, value, ..
+            } => {
+                value.x = f[0].max(min.x).min(max.x);
+                value.y = f[1].max(min.y).min(max.y);
+                value.z = f[2].max(min.z).min(max.z);
+                value.w = f[3].max(min.w).min(max.w);
+            }
+            Slider::ColorAlpha { value, .. } => *value = Vector4::from([f[0], f[1], f[2], f[3]]),
+            Slider::IVec2 {
+                min,
+                max,
+                step,
+                value,
+                ..
+            } => {
+                value.x = snap_int(c[0], min.x, max.x, *step);
+                value.y = snap_int(c[1], min.y, max.y, *step);
+            }
+            Slider::IVec3 {
+                min,
+                max,
+                step,
+                value,
+                ..
+            } => {
+                value.x = snap_int(c[0], min.x, max.x, *step);
+                value.y = snap_int(c[1], min.y, max.y, *step);
+                value.z = snap_int(c[2], min.z, max.z, *step);
+            }
+            Slider::UVec2 {
+                min,
+                max,
+                step,
+                value,
+                ..
+            } => {
+                value.x = snap_int(c[0], min.x, max.x, *step);
+                value.y = snap_int(c[1], min.y, max.y, *step);
+            }
+            Slider::UVec3 {
+                min,
+                max,
+                step,
+                value,
+                ..
+            } => {
+                value.x = snap_int(c[0], min.x, max.x, *step);
+                value.y = snap_int(c[1], min.y, max.y, *step);
+                value.z = snap_int(c[2], min.z, max.z, *step);
+            }
+            Slider::Mat2 { value, .. } => *value = ColumnMatrix2::from(columns::<2>(&f)),
+            Slider::Mat3 { value, .. } => *value = ColumnMatrix3::from(columns::<3>(&f)),
+            Slider::Mat4 { value, .. } => *value = ColumnMatrix4::from(columns::<4>(&f)),
+            Slider::Array { items, .. } => {
+                let mut rest = c;
+                for item in items.iter_mut() {
+                    let len = item.components().map_or(0, |it| it.len());
+                    let (head, tail) = rest.split_at(len);
+                    item.set_components(head);
+                    rest = tail;
+                }
+            }
+            Slider::Gradient { .. } | Slider::Curve { .. } => {}
+        }
+    }
+
+    /// The value to save in a preset
+    pub fn value(&self) -> ParamValue {
+        match self {
+            Slider::Gradient { value, .. } => ParamValue::Gradient(value.clone()),
+            Slider::Curve { value, .. } => ParamValue::Curve(value.clone()),
+            _ if self.is_integer() => ParamValue::Integers(
+                self.components()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|it| *it as i64)
+                    .collect(),
+            ),
+            _ => ParamValue::Components(self.components().unwrap_or_default()),
+        }
+    }
+
+    /// Whether all the components are integers, they are saved as such
+    fn is_integer(&self) -> bool {
+        match self {
+            Slider::Int { .. }
+            | Slider::UInt { .. }
+            | Slider::Bool { .. }
+            | Slider::Enum { .. }
+            | Slider::IVec2 { .. }
+            | Slider::IVec3 { .. }
+            | Slider::UVec2 { .. }
+            | Slider::UVec3 { .. } => true,
+            Slider::Array { items, .. } => items.iter().all(Slider::is_integer),
+            _ => false,
+        }
+    }
+
+    /// Set a value saved in a preset, kept in the bounds of this slider.
+    /// Returns false if the value doesn't fit this slider.
+    pub fn set_value(&mut self, value: &ParamValue) -> bool {
+        match (self, value) {
+            (Slider::Gradient { value, .. }, ParamValue::Gradient(stops)) if !stops.is_empty() => {
+                *value = stops.clone();
+            }
+            (Slider::Curve { value, .. }, ParamValue::Curve(points)) if !points.is_empty() => {
+                *value = points.clone();
+            }
+            // Arrays must also have the same length
+            (this, value) => match (this.components(), value.components()) {
+                (Some(current), Some(components)) if current.len() == components.len() => {
+                    this.set_components(&components)
+                }
+                _ => return false,
+            },
+        }
+        true
+    }
+
+    /// Take the value of a slider of the same type, kept in the bounds of this one.
+    /// Returns false if the types don't match.
+    pub fn restore(&mut self, old: &Slider) -> bool {
+        mem::discriminant(self) == mem::discriminant(old) && self.set_value(&old.value())
+    }
+
+    /// Set the value between two saved values. Colors are mixed in the Oklab space,
+    /// bools and enums switch at the midpoint.
+    pub fn morph(&mut self, a: &ParamValue, b: &ParamValue, t: f32) {
+        // Integers are mixed like the other numbers
+        let numbers = |it: &ParamValue| {
+            it.components()
+                .map_or_else(|| it.clone(), ParamValue::Components)
+        };
+        let (a, b) = (&numbers(a), &numbers(b));
+        let nearest = if t < 0.5 { a } else { b };
+        if matches!(self, Slider::Bool { .. } | Slider::Enum { .. }) {
+            self.set_value(nearest);
+            return;
+        }
+        let value = match (&mut *self, a, b) {
+            (Slider::Array { items, .. }, ParamValue::Components(a), ParamValue::Components(b))
+                if a.len() == b.len() =>
+            {
+                // Each item is morphed on its own so arrays of colors or enums work too
+                let (mut a, mut b) = (&a[..], &b[..]);
+                for item in items.iter_mut() {
+                    let len = item.components().map_or(0, |it| it.len()).min(a.len());
+                    let (item_a, rest_a) = a.split_at(len);
+                    let (item_b, rest_b) = b.split_at(len);
+                    item.morph(
+                        &ParamValue::Components(item_a.to_vec()),
+                        &ParamValue::Components(item_b.to_vec()),
+                        t,
+                    );
+                    a = rest_a;
+                    b = rest_b;
+                }
+                return;
+            }
+            (Slider::Color { .. }, ParamValue::Components(a), ParamValue::Components(b))
+            | (Slider::ColorAlpha { .. }, ParamValue::Components(a), ParamValue::Components(b))
+                if a.len() == b.len() && a.len() >= 3 =>
+            {
+                let a: Vec<f32> = a.iter().map(|it| *it as f32).collect();
+                let b: Vec<f32> = b.iter().map(|it| *it as f32).collect();
+                ParamValue::Components(mix_color(&a, &b, t).into_iter().map(f64::from).collect())
+            }
+            (Slider::Gradient { .. }, ParamValue::Gradient(a), ParamValue::Gradient(b))
+                if a.len() == b.len() =>
+            {
+                ParamValue::Gradient(
+                    a.iter()
+                        .zip(b.iter())
+                        .map(|(a, b)| {
+                            let ca: [f32; 4] = a.color.into();
+                            let cb: [f32; 4] = b.color.into();
+                            let color = mix_color(&ca, &cb, t);
+                            GradientStop {
+                                position: mix(a.position, b.position, t),
+                                color: [color[0], color[1], color[2], color[3]].into(),
+                            }
+                        })
+                        .collect(),
+                )
+            }
+            (Slider::Curve { .. }, ParamValue::Curve(a), ParamValue::Curve(b))
+                if a.len() == b.len() =>
+            {
+                ParamValue::Curve(
+                    a.iter()
+                        .zip(b.iter())
+                        .map(|(a, b)| Vector2::from([mix(a.x, b.x, t), mix(a.y, b.y, t)]))
+                        .collect(),
+                )
+            }
+            // Mixed in double precision so large integers are only rounded once, when set
+            (_, ParamValue::Components(a), ParamValue::Components(b)) if a.len() == b.len() => {
+                ParamValue::Components(
+                    a.iter()
+                        .zip(b.iter())
+                        .map(|(a, b)| a + (b - a) * f64::from(t))
+                        .collect(),
+                )
+            }
+            // Gradients and curves with a different number of points can't be mixed
+            _ => nearest.clone(),
+        };
+        self.set_value(&value);
+    }
+}
+
+impl Slider {
+    /// Bounds of each component used to pick random values, None if the slider has no bounds
+    /// or unbounded components. Bounds of integers are extended by half a step so every value
+    /// is as likely after rounding.
+    fn random_bounds(&self) -> Option<Vec<(f32, f32)>> {
+        let pairs =
+            |min: &[f32], max: &[f32]| min.iter().copied().zip(max.iter().copied()).collect();
+        let discrete = |min: f32, max: f32| vec![(min - 0.5, max + 0.5)];
+        let discretes = |min: &[f32], max: &[f32]| -> Vec<(f32, f32)> {
+            min.iter()
+                .zip(max.iter())
+                .map(|(min, max)| (min - 0.5, max + 0.5))
+                .collect()
+        };
+        let bounds: Vec<(f32, f32)> = match self {
+            Slider::Float { min, max, .. } => vec![(*min, *max)],
+            Slider::Int { min, max, .. } => discrete(*min as f32, *max as f32),
+            Slider::UInt { min, max, .. } => discrete(*min as f32, *max as f32),
+            Slider::Bool { .. } => discrete(0.0, 1.0),
+            Slider::Enum { options, .. } => discrete(0.0, options.len() as f32 - 1.0),
+            Slider::Vec2 { min, max, .. } | Slider::Pad { min, max, .. } => {
+                pairs(min.as_ref(), max.as_ref())
+            }
+            Slider::Vec3 { min, max, .. } => pairs(min.as_ref(), max.as_ref()),
+            Slider::Vec4 { min, max, .. } => pairs(min.as_ref(), max.as_ref()),
+            Slider::IVec2 { min, max, .. } => {
+                discretes(&[min.x as f32, min.y as f32], &[max.x as f32, max.y as f32])
+            }
+            Slider::IVec3 { min, max, .. } => discretes(
+                &[min.x as f32, min.y as f32, min.z as f32],
+                &[max.x as f32, max.y as f32, max.z as f32],
+            ),
+            Slider::UVec2 { min, max, .. } => {
+                discretes(&[min.x as f32, min.y as f32], &[max.x as f32, max.y as f32])
+            }
+            Slider::UVec3 { min, max, .. } => discretes(
+                &[min.x as f32, min.y as f32, min.z as f32],
+                &[max.x as f32, max.y as f32, max.z as f32],
+            ),
+            Slider::Direction { .. } => vec![(-1.0, 1.0); 3],
+            Slider::Euler { .. } => vec![(-std::f32::consts::PI, std::f32::consts::PI); 3],
+            Slider::Color { .. } => vec![(0.0, 1.0); 3],
+            Slider::ColorAlpha { .. } => vec![(0.0, 1.0); 4],
+            _ => return None,
+        };
+        // Vector bounds are infinite unless given
+        if bounds
+            .iter()
+            .all(|(min, max)| min.is_finite() && max.is_finite())
+        {
+            Some(bounds)
+        } else {
+            None
+        }
+    }
+
+    /// Bounds of each component an oscillator can drive, the items of arrays are flattened.
+    /// Unbounded components oscillate around their value.
+    pub fn lfo_ranges(&self) -> Option<Vec<(f32, f32)>> {
+        let pairs = |min: &[f32], max: &[f32]| -> Vec<(f32, f32)> {
+            min.iter().copied().zip(max.iter().copied()).collect()
+        };
+        let mut ranges = match self {
+            Slider::Float { min, max, .. } => vec![(*min, *max)],
+            Slider::Int { min, max, .. } => vec![(*min as f32, *max as f32)],
+            Slider::UInt { min, max, .. } => vec![(*min as f32, *max as f32)],
+            Slider::Vec2 { min, max, .. } | Slider::Pad { min, max, .. } => {
+                pairs(min.as_ref(), max.as_ref())
+            }
+            Slider::Vec3 { min, max, .. } => pairs(min.as_ref(), max.as_ref()),
+            Slider::Vec4 { min, max, .. } => pairs(min.as_ref(), max.as_ref()),
+            Slider::IVec2 { min, max, .. } => {
+                pairs(&[min.x as f32, min.y as f32], &[max.x as f32, max.y as f32])
+            }
+            Slider::IVec3 { min, max, .. } => pairs(
+                &[min.x as f32, min.y as f32, min.z as f32],
+                &[max.x as f32, max.y as f32, max.z as f32],
+            ),
+            Slider::UVec2 { min, max, .. } => {
+                pairs(&[min.x as f32, min.y as f32], &[max.x as f32, max.y as f32])
+            }
+            Slider::UVec3 { min, max, .. } => pairs(
+                &[min.x as f32, min.y as f32, min.z as f32],
+                &[max.x as f32, max.y as f32, max.z as f32],
+            ),
+            Slider::Euler { .. } => vec![(-std::f32::consts::PI, std::f32::consts::PI); 3],
+            Slider::Color { .. } => vec![(0.0, 1.0); 3],
+            Slider::ColorAlpha { .. } => vec![(0.0, 1.0); 4],
+            Slider::Mat2 { .. } => vec![(f32::NEG_INFINITY, f32::INFINITY); 4],
+            Slider::Mat3 { .. } => vec![(f32::NEG_INFINITY, f32::INFINITY); 9],
+            Slider::Mat4 { .. } => vec![(f32::NEG_INFINITY, f32::INFINITY); 16],
+            // Bools switch at the midpoint and enums go through their options
+            Slider::Bool { .. } => vec![(0.0, 1.0)],
+            Slider::Enum { options, .. } => vec![(0.0, options.len() as f32 - 1.0)],
+            Slider::Array { items, .. } => {
+                return items
+                    .iter()
+                    .map(Slider::lfo_ranges)
+                    .collect::<Option<Vec<_>>>()
+                    .map(|it| it.concat())
+            }
+            _ => return None,
+        };
+        for (range, value) in ranges.iter_mut().zip(self.components()?) {
+            if !range.0.is_finite() || !range.1.is_finite() {
+                *range = (value as f32 - 1.0, value as f32 + 1.0);
+            }
+        }
+        Some(ranges)
+    }
+
+    /// Pick a random value within the bounds
+    pub fn randomize(&mut self, rng: &mut Rng) {
+        match self {
+            Slider::Array { items, .. } => {
+                for item in items.iter_mut() {
+                    item.randomize(rng);
+                }
+            }
+            // Stops and points keep their position
+            Slider::Gradient { value, .. } => {
+                for stop in value.iter_mut() {
+                    stop.color.x = rng.next_f32();
+                    stop.color.y = rng.next_f32();
+                    stop.color.z = rng.next_f32();
+                }
+            }
+            Slider::Curve {
+                min, max, value, ..
+            } => {
+                for point in value.iter_mut() {
+                    point.y = mix(*min, *max, rng.next_f32());
+                }
+            }
+            _ => {
+                if let Some(bounds) = self.random_bounds() {
+                    let value: Vec<f64> = bounds
+                        .iter()
+                        .map(|(min, max)| mix(*min, *max, rng.next_f32()) as f64)
+                        .collect();
+                    self.set_components(&value);
+                }
+            }
+        }
+    }
+
+    /// Move the value randomly by a fraction of its range, or of its magnitude for sliders
+    /// without bounds. Bools and enums change with a probability of `amount`.
+    pub fn mutate(&mut self, rng: &mut Rng, amount: f32) {
+        match self {
+            Slider::Array { items, .. } => {
+                for item in items.iter_mut() {
+                    item.mutate(rng, amount);
+                }
+            }
+            Slider::Bool { .. } | Slider::Enum { .. } => {
+                if rng.next_f32() < amount {
+                    self.randomize(rng);
+                }
+            }
+            Slider::Gradient { value, .. } => {
+                for stop in value.iter_mut() {
+                    let jitter =
+                        |c: f32, rng: &mut Rng| (c + rng.next_signed() * amount).clamp(0.0, 1.0);
+                    stop.color.x = jitter(stop.color.x, rng);
+                    stop.color.y = jitter(stop.color.y, rng);
+                    stop.color.z = jitter(stop.color.z, rng);
+                }
+            }
+            Slider::Curve {
+                min, max, value, ..
+            } => {
+                for point in value.iter_mut() {
+                    point.y = (point.y + rng.next_signed() * amount * (*max - *min))
+                        .max(*min)
+                        .min(*max);
+                }
+            }
+            _ => {
+                let mut value = match self.components() {
+                    Some(value) => value,
+                    None => return,
+                };
+                let bounds = self.random_bounds();
+                for (i, c) in value.iter_mut().enumerate() {
+                    let range = match &bounds {
+                        Some(bounds) => (bounds[i].1 - bounds[i].0) as f64,
+                        None => c.abs().max(1.0),
+                    };
+                    *c += (rng.next_signed() * amount) as f64 * range;
+                }
+                self.set_components(&value);
+            }
+        }
+    }
+}
+
+fn mix(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Mix two linear rgb colors in the Oklab space, an alpha component is mixed linearly
+fn mix_color(a: &[f32], b: &[f32], t: f32) -> Vec<f32> {
+    let lab_a = linear_to_oklab([a[0], a[1], a[2]]);
+    let lab_b = linear_to_oklab([b[0], b[1], b[2]]);
+    let rgb = oklab_to_linear([
+        mix(lab_a[0], lab_b[0], t),
+        mix(lab_a[1], lab_b[1], t),
+        mix(lab_a[2], lab_b[2], t),
+    ]);
+    let mut color = rgb.to_vec();
+    color.extend(a.iter().zip(b.iter()).skip(3).map(|(a, b)| mix(*a, *b, t)));
+    color
+}
+
+/// Source : https://bottosson.github.io/posts/oklab/
+fn linear_to_oklab([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = (0.412_221_47 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
 
-/// Traverses the ast and extract useful data while converting the ast to valid glsl source
+fn oklab_to_linear([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let l_ = l + 0.396_337_78 * a + 0.215_803_76 * b;
+    let m_ = l - 0.105_561_346 * a - 0.063_854_17 * b;
+    let s_ = l - 0.089_484_18 * a - 1.291_485_5 * b;
+    let (l, m, s) = (l_ * l_ * l_, m_ * m_ * m_, s_ * s_ * s_);
+    [
+        4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+        -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+        -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+    ]
+}
+
+/// Columns of a square matrix from its column major components
+fn columns<const N: usize>(c: &[f32]) -> [[f32; N]; N] {
+    let mut columns = [[0.0; N]; N];
+    for (i, column) in columns.iter_mut().enumerate() {
+        column.copy_from_slice(&c[i * N..(i + 1) * N]);
+    }
+    columns
+}
+
+/// Linear interpolation between the two stops around t
+pub fn sample_gradient(stops: &[GradientStop], t: f32) -> Vector4<f32> {
+    let next = stops.iter().position(|it| it.position > t);
+    match next {
+        None => stops.last().map_or([0.0; 4].into(), |it| it.color),
+        Some(0) => stops[0].color,
+        Some(i) => {
+            let (a, b) = (&stops[i - 1], &stops[i]);
+            let f = (t - a.position) / (b.position - a.position);
+            let (ca, cb): ([f32; 4], [f32; 4]) = (a.color.into(), b.color.into());
+            let mut color = ca;
+            for (c, b) in color.iter_mut().zip(cb.iter()) {
+                *c = mix(*c, *b, f);
+            }
+            color.into()
+        }
+    }
+}
+
+/// Catmull-Rom spline going through every point, flat before the first and after the last
+pub fn sample_curve(points: &[Vector2<f32>], t: f32) -> f32 {
+    let next = points.iter().position(|it| it.x > t);
+    let i = match next {
+        None => return points.last().map_or(0.0, |it| it.y),
+        Some(0) => return points[0].y,
+        Some(i) => i,
+    };
+    let (p1, p2) = (points[i - 1], points[i]);
+    let p0 = if i >= 2 { points[i - 2].y } else { p1.y };
+    let p3 = points.get(i + 1).map_or(p2.y, |it| it.y);
+    let f = (t - p1.x) / (p2.x - p1.x);
+    let (f2, f3) = (f * f, f * f * f);
+    0.5 * (2.0 * p1.y
+        + (p2.y - p0) * f
+        + (2.0 * p0 - 5.0 * p1.y + 4.0 * p2.y - p3) * f2
+        + (3.0 * p1.y - p0 - 3.0 * p2.y + p3) * f3)
+}
+
+impl Slider {
+    /// Create a pad, it needs bounds so unbounded components map 0 to 1
+    pub fn pad(
+        name: String,
+        min: Vector2<f32>,
+        max: Vector2<f32>,
+        speed: Vector2<f32>,
+        canvas: Option<CanvasCoords>,
+        init: Vector2<f32>,
+    ) -> Self {
+        let finite_or = |v: f32, default| if v.is_finite() { v } else { default };
+        Slider::Pad {
+            name,
+            min: Vector2::from([finite_or(min.x, 0.0), finite_or(min.y, 0.0)]),
+            max: Vector2::from([finite_or(max.x, 1.0), finite_or(max.y, 1.0)]),
+            speed,
+            canvas,
+            value: init,
+            default: init,
+        }
+    }
+
+    /// Create a direction, the vector is normalized and faces the viewer by default
+    pub fn direction(name: String, init: Vector3<f32>) -> Self {
+        let length = (init.x * init.x + init.y * init.y + init.z * init.z).sqrt();
+        let init = if length > 0.0 {
+            Vector3::from([init.x / length, init.y / length, init.z / length])
+        } else {
+            Vector3::from([0.0, 0.0, 1.0])
+        };
+        Slider::Direction {
+            name,
+            value: init,
+            default: init,
+        }
+    }
+}
+
+/// Write zeros until the writer is aligned, every param is at least 4 bytes aligned
+fn pad<W: std::io::Write>(writer: &mut std140::Writer<W>, alignment: usize) {
+    let padding = (alignment - writer.len() % alignment) % alignment;
+    for _ in 0..padding / 4 {
+        writer.write(&0u32).unwrap();
+    }
+}
+
+/// std140 lays out matrices as arrays of columns, each column is padded to a vec4
+trait PaddedColumns {
+    fn padded_columns(&self) -> Vec<Vector4<f32>>;
+}
+
+impl PaddedColumns for ColumnMatrix2<f32> {
+    fn padded_columns(&self) -> Vec<Vector4<f32>> {
+        [self.x, self.y]
+            .iter()
+            .map(|c| Vector4::from([c.x, c.y, 0.0, 0.0]))
+            .collect()
+    }
+}
+
+impl PaddedColumns for ColumnMatrix3<f32> {
+    fn padded_columns(&self) -> Vec<Vector4<f32>> {
+        [self.x, self.y, self.z]
+            .iter()
+            .map(|c| Vector4::from([c.x, c.y, c.z, 0.0]))
+            .collect()
+    }
+}
+
+impl PaddedColumns for ColumnMatrix4<f32> {
+    fn padded_columns(&self) -> Vec<Vector4<f32>> {
+        vec![self.x, self.y, self.z, self.w]
+    }
+}
+
+/// A shader param and how it is displayed
+pub struct Param {
+    pub slider: Slider,
+    /// Name displayed instead of the name in the source
+    pub label: Option<String>,
+    /// Shown when hovering the param
+    pub description: Option<String>,
+    /// Collapsible section the param is displayed in
+    pub group: Option<String>,
+    /// Animation of the value, sorted by time
+    pub keyframes: Vec<Keyframe>,
+    /// Protects the value from randomize and mutate
+    pub locked: bool,
+    /// Oscillators driving the components of the value, empty if there are none
+    pub lfos: Vec<Option<Lfo>>,
+}
+
+impl Param {
+    pub fn new(slider: Slider) -> Self {
+        Self {
+            slider,
+            label: None,
+            description: None,
+            group: None,
+            keyframes: Vec::new(),
+            locked: false,
+            lfos: Vec::new(),
+        }
+    }
+
+    /// The name to display
+    pub fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or_else(|| self.slider.name())
+    }
+
+    /// Keyframe the current value at the given time, replacing a keyframe at the same time
+    pub fn set_keyframe(&mut self, time: f32) {
+        let value = match self.slider.components() {
+            Some(value) => value,
+            None => return,
+        };
+        match self
+            .keyframes
+            .iter_mut()
+            .find(|it| (it.time - time).abs() < 0.001)
+        {
+            Some(keyframe) => keyframe.value = value,
+            None => {
+                let pos = self
+                    .keyframes
+                    .iter()
+                    .position(|it| it.time > time)
+                    .unwrap_or(self.keyframes.len());
+                self.keyframes.insert(
+                    pos,
+                    Keyframe {
+                        time,
+                        value,
+                        interpolation: Interpolation::Linear,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Keep keyframes sorted after their time has been edited
+    pub fn sort_keyframes(&mut self) {
+        self.keyframes
+            .sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+    }
+
+    /// Set the value from the keyframes at the given time
+    pub fn animate(&mut self, time: f32) {
+        if let Some(value) = timeline::evaluate(&self.keyframes, time) {
+            self.slider.set_components(&value);
+        }
+    }
+
+    /// Set the modulated components from their oscillator at the given time
+    pub fn modulate(&mut self, time: f32) {
+        if self.lfos.iter().all(Option::is_none) {
+            return;
+        }
+        if let Some(mut value) = self.slider.components() {
+            for (component, lfo) in value.iter_mut().zip(self.lfos.iter()) {
+                if let Some(lfo) = lfo {
+                    *component = lfo.sample(time) as f64;
+                }
+            }
+            self.slider.set_components(&value);
+        }
+    }
+}
+
+impl Slider {
+    /// Check that no min is greater than its max and that integers start within their bounds,
+    /// the ui and the clamping rely on it
+    pub fn check_bounds(&self) -> Result<(), String> {
+        let ordered =
+            |min: &[f32], max: &[f32]| min.iter().zip(max.iter()).all(|(min, max)| min <= max);
+        let ordered = match self {
+            Slider::Float { min, max, .. } | Slider::Curve { min, max, .. } => min <= max,
+            Slider::Int { min, max, .. } => min <= max,
+            Slider::UInt { min, max, .. } => min <= max,
+            Slider::Vec2 { min, max, .. } | Slider::Pad { min, max, .. } => {
+                ordered(min.as_ref(), max.as_ref())
+            }
+            Slider::Vec3 { min, max, .. } => ordered(min.as_ref(), max.as_ref()),
+            Slider::Vec4 { min, max, .. } => ordered(min.as_ref(), max.as_ref()),
+            Slider::IVec2 { min, max, .. } => min.x <= max.x && min.y <= max.y,
+            Slider::IVec3 { min, max, .. } => min.x <= max.x && min.y <= max.y && min.z <= max.z,
+            Slider::UVec2 { min, max, .. } => min.x <= max.x && min.y <= max.y,
+            Slider::UVec3 { min, max, .. } => min.x <= max.x && min.y <= max.y && min.z <= max.z,
+            Slider::Array { items, .. } => return items.iter().try_for_each(Slider::check_bounds),
+            _ => true,
+        };
+        if !ordered {
+            return Err(format!(
+                "Param '{}' has a min greater than its max",
+                self.name()
+            ));
+        }
+        let within = match self {
+            Slider::Int {
+                min, max, default, ..
+            } => min <= default && default <= max,
+            Slider::UInt {
+                min, max, default, ..
+            } => min <= default && default <= max,
+            Slider::IVec2 {
+                min, max, default, ..
+            } => contains(min.as_ref(), max.as_ref(), default.as_ref()),
+            Slider::IVec3 {
+                min, max, default, ..
+            } => contains(min.as_ref(), max.as_ref(), default.as_ref()),
+            Slider::UVec2 {
+                min, max, default, ..
+            } => contains(min.as_ref(), max.as_ref(), default.as_ref()),
+            Slider::UVec3 {
+                min, max, default, ..
+            } => contains(min.as_ref(), max.as_ref(), default.as_ref()),
+            Slider::Enum {
+                options, default, ..
+            } => {
+                if *default < 0 || *default as usize >= options.len() {
+                    return Err(format!(
+                        "Param '{}' starts at option {} but has {} options",
+                        self.name(),
+                        default,
+                        options.len()
+                    ));
+                }
+                true
+            }
+            _ => true,
+        };
+        if within {
+            Ok(())
+        } else {
+            Err(format!(
+                "Param '{}' has an initial value outside of its bounds",
+                self.name()
+            ))
+        }
+    }
+}
+
+/// Snap a value to the closest `min + k * step` within the bounds, a step of 0 doesn't snap
+fn snap(value: f64, min: f64, max: f64, step: f64) -> f64 {
+    let value = value.max(min).min(max);
+    if step <= 0.0 || !min.is_finite() {
+        return value;
+    }
+    let snapped = min + ((value - min) / step).round() * step;
+    // The closest step can be past the max when the range isn't a multiple of the step
+    if snapped > max {
+        snapped - step
+    } else {
+        snapped
+    }
+}
+
+/// Snap and round a value to an integer type
+fn snap_int<T: Copy + Into<f64> + TryFrom<i64>>(value: f64, min: T, max: T, step: T) -> T {
+    let snapped = snap(value, min.into(), max.into(), step.into()).round() as i64;
+    T::try_from(snapped).unwrap_or(min)
+}
+
+/// Whether every component of a value is within its bounds
+fn contains<T: PartialOrd>(min: &[T], max: &[T], value: &[T]) -> bool {
+    value
+        .iter()
+        .zip(min.iter().zip(max.iter()))
+        .all(|(value, (min, max))| min <= value && value <= max)
+}
+
+/// Data extracted from the shader source before compilation
 #[derive(Default)]
 pub struct ShaderMetadata {
-    pub sliders: Vec<Slider>,
+    /// Params in the order they are declared in the params block
+    pub params: Vec<Param>,
     pub still_image: bool,
+    /// Saved sets of values, loaded from the presets file of the shader
+    pub presets: Vec<Preset>,
+    /// Why the presets file couldn't be loaded, it isn't overwritten until it is fixed
+    pub presets_error: Option<String>,
+    /// Whether the lookup textures are up to date with the gradients and curves
+    luts_baked: bool,
 }
 
 impl ShaderMetadata {
+    /// Add a param at the end of the params block, its bounds are checked.
+    /// Params of a group must be declared together, the sections would be split otherwise.
+    pub fn add_param(&mut self, param: Param) -> Result<(), String> {
+        param.slider.check_bounds()?;
+        if let Some(group) = param.group.as_ref() {
+            let last_group = self.params.last().and_then(|it| it.group.as_ref());
+            if last_group != Some(group)
+                && self
+                    .params
+                    .iter()
+                    .any(|it| it.group.as_ref() == Some(group))
+            {
+                return Err(format!(
+                    "Params of group '{}' must be declared together",
+                    group
+                ));
+            }
+        }
+        self.params.push(param);
+        Ok(())
+    }
+
+    /// Set the first param with the canvas setting from a position on the canvas,
+    /// normalized between 0 and 1
+    pub fn drag_on_canvas(&mut self, x: f32, y: f32, resolution: Vector2<u32>) {
+        for param in self.params.iter_mut() {
+            if let Slider::Pad {
+                min,
+                max,
+                canvas: Some(coords),
+                value,
+                ..
+            } = &mut param.slider
+            {
+                let (x, y) = match coords {
+                    CanvasCoords::Pixels => (x * resolution.x as f32, y * resolution.y as f32),
+                    CanvasCoords::Normalized => (x, y),
+                };
+                value.x = x.max(min.x).min(max.x);
+                value.y = y.max(min.y).min(max.y);
+                return;
+            }
+        }
+    }
+
+    /// Carry over the values, keyframes, oscillators and locks of the params that still have
+    /// the same name and type after a reload. Other params keep their defaults.
+    pub fn restore_params(&mut self, old: &ShaderMetadata) {
+        for param in self.params.iter_mut() {
+            let old = old
+                .params
+                .iter()
+                .find(|it| it.slider.name() == param.slider.name());
+            if let Some(old) = old {
+                if param.slider.restore(&old.slider) {
+                    param.keyframes = old.keyframes.clone();
+                    param.lfos = old.lfos.clone();
+                    param.locked = old.locked;
+                }
+            }
+        }
+        self.invalidate_luts();
+    }
+
+    /// Current values of all the params, to save as a preset
+    pub fn values(&self) -> HashMap<String, ParamValue> {
+        self.sliders()
+            .map(|it| (it.name().to_string(), it.value()))
+            .collect()
+    }
+
+    /// Oscillators of the params, to save as a preset
+    pub fn lfos(&self) -> HashMap<String, Vec<Option<Lfo>>> {
+        self.params
+            .iter()
+            .filter(|it| it.lfos.iter().any(Option::is_some))
+            .map(|it| (it.slider.name().to_string(), it.lfos.clone()))
+            .collect()
+    }
+
+    /// Set the oscillators of all the params from a preset, they must match the components
+    pub fn set_lfos(&mut self, lfos: &HashMap<String, Vec<Option<Lfo>>>) {
+        for param in self.params.iter_mut() {
+            let count = param.slider.lfo_ranges().map_or(0, |it| it.len());
+            param.lfos = lfos
+                .get(param.slider.name())
+                .filter(|it| it.len() == count)
+                .cloned()
+                .unwrap_or_default();
+        }
+    }
+
+    /// Set the params from saved values, params not in the values are left untouched
+    pub fn set_values(&mut self, values: &HashMap<String, ParamValue>) {
+        for param in self.params.iter_mut() {
+            if let Some(value) = values.get(param.slider.name()) {
+                param.slider.set_value(value);
+            }
+        }
+        self.invalidate_luts();
+    }
+
+    /// Set the params between the values of two presets, given by index.
+    /// Params missing from one of the presets are left untouched.
+    pub fn morph(&mut self, from: usize, to: usize, t: f32) {
+        let (from, to) = match (self.presets.get(from), self.presets.get(to)) {
+            (Some(from), Some(to)) => (&from.values, &to.values),
+            _ => return,
+        };
+        for param in self.params.iter_mut() {
+            let name = param.slider.name();
+            if let (Some(a), Some(b)) = (from.get(name), to.get(name)) {
+                param.slider.morph(a, b, t);
+            }
+        }
+        self.invalidate_luts();
+    }
+
+    /// Pick random values for the unlocked params
+    pub fn randomize(&mut self, seed: u64) {
+        let mut rng = Rng::new(seed);
+        for param in self.params.iter_mut().filter(|it| !it.locked) {
+            param.slider.randomize(&mut rng);
+        }
+        self.invalidate_luts();
+    }
+
+    /// Move the values of the unlocked params randomly, `amount` is a fraction of their range
+    pub fn mutate(&mut self, seed: u64, amount: f32) {
+        let mut rng = Rng::new(seed);
+        for param in self.params.iter_mut().filter(|it| !it.locked) {
+            param.slider.mutate(&mut rng, amount);
+        }
+        self.invalidate_luts();
+    }
+
+    /// Evaluate the animated params at the given time on the timeline
+    pub fn animate(&mut self, time: f32) {
+        for param in self.params.iter_mut() {
+            param.animate(time);
+        }
+    }
+
+    /// Update the bools bound to a key when it is pressed or released
+    pub fn key_event(&mut self, key: VirtualKeyCode, pressed: bool) {
+        for param in self.params.iter_mut() {
+            if let Slider::Bool {
+                key: Some(binding),
+                value,
+                ..
+            } = &mut param.slider
+            {
+                if binding.key != key {
+                    continue;
+                }
+                if binding.hold {
+                    *value = if pressed { 1 } else { 0 };
+                } else if pressed {
+                    *value = if *value == 0 { 1 } else { 0 };
+                }
+            }
+        }
+    }
+
+    /// Evaluate the oscillators of the params at the given shader time
+    pub fn modulate(&mut self, time: f32) {
+        for param in self.params.iter_mut() {
+            param.modulate(time);
+        }
+    }
+
+    /// Iterate over the sliders of all the params
+    pub fn sliders(&self) -> impl Iterator<Item = &Slider> {
+        self.params.iter().map(|it| &it.slider)
+    }
+
     pub fn params_buffer_size(&self) -> u64 {
         self.params_buffer().len() as u64
     }
@@ -81,17 +1354,50 @@ impl ShaderMetadata {
         let mut bytes = Vec::new();
         let mut writer = std140::Writer::new(&mut bytes);
 
-        for slider in self.sliders.iter() {
+        for slider in self.sliders() {
             slider.write(&mut writer);
         }
 
         bytes
     }
 
+    /// Number of lookup textures used by the shader
+    pub fn lut_count(&self) -> u32 {
+        self.sliders()
+            .filter(|it| matches!(it, Slider::Gradient { .. } | Slider::Curve { .. }))
+            .count() as u32
+    }
+
+    /// Texels of all the lookup textures, in the order they are bound
+    pub fn lut_buffer(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for texel in self.sliders().filter_map(Slider::bake).flatten() {
+            for component in texel.iter() {
+                bytes.extend_from_slice(&component.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// The lookup textures must be baked again after a gradient or a curve changed
+    pub fn invalidate_luts(&mut self) {
+        self.luts_baked = false;
+    }
+
+    /// Texels of all the lookup textures, only if they changed since the last call
+    pub fn changed_lut_buffer(&mut self) -> Option<Vec<u8>> {
+        if self.luts_baked {
+            return None;
+        }
+        self.luts_baked = true;
+        Some(self.lut_buffer())
+    }
+
     pub fn reset_params(&mut self) {
-        for slider in self.sliders.iter_mut() {
-            slider.reset();
+        for param in self.params.iter_mut() {
+            param.slider.reset();
         }
+        self.invalidate_luts();
     }
 }
 
@@ -103,3 +1409,102 @@ pub struct Shader {
     /// Shader metadata extracted before compilation
     pub metadata: Option<ShaderMetadata>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn float(value: f32) -> Slider {
+        Slider::Float {
+            name: "float".to_string(),
+            min: 0.0,
+            max: 1.0,
+            step: 0.0,
+            log: false,
+            value,
+            default: value,
+        }
+    }
+
+    fn vec3(value: [f32; 3]) -> Slider {
+        Slider::Vec3 {
+            name: "vec3".to_string(),
+            min: [f32::NEG_INFINITY; 3].into(),
+            max: [f32::INFINITY; 3].into(),
+            speed: [0.01; 3].into(),
+            value: value.into(),
+            default: value.into(),
+        }
+    }
+
+    /// std140 layout of the sliders written one after the other, as floats
+    fn std140(sliders: &[Slider]) -> Vec<f32> {
+        let mut bytes = Vec::new();
+        let mut writer = std140::Writer::new(&mut bytes);
+        for slider in sliders {
+            slider.write(&mut writer);
+        }
+        bytes
+            .chunks(4)
+            .map(|it| f32::from_le_bytes([it[0], it[1], it[2], it[3]]))
+            .collect()
+    }
+
+    #[test]
+    fn mat3_columns_are_padded() {
+        let columns = [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]];
+        let mat3 = Slider::Mat3 {
+            name: "mat3".to_string(),
+            value: ColumnMatrix3::from(columns),
+            default: ColumnMatrix3::from(columns),
+        };
+        #[rustfmt::skip]
+        assert_eq!(
+            std140(&[float(0.5), mat3, float(0.25)]),
+            vec![
+                0.5, 0.0, 0.0, 0.0,
+                1.0, 2.0, 3.0, 0.0,
+                4.0, 5.0, 6.0, 0.0,
+                7.0, 8.0, 9.0, 0.0,
+                0.25,
+            ]
+        );
+    }
+
+    #[test]
+    fn float_array_items_are_16_bytes() {
+        let array = Slider::Array {
+            name: "floats".to_string(),
+            items: vec![float(1.0), float(2.0), float(3.0)],
+        };
+        #[rustfmt::skip]
+        assert_eq!(
+            std140(&[array, float(0.5)]),
+            vec![
+                1.0, 0.0, 0.0, 0.0,
+                2.0, 0.0, 0.0, 0.0,
+                3.0, 0.0, 0.0, 0.0,
+                0.5,
+            ]
+        );
+    }
+
+    #[test]
+    fn vec3_array_items_are_16_bytes() {
+        let array = Slider::Array {
+            name: "vec3s".to_string(),
+            items: vec![vec3([1.0, 2.0, 3.0]), vec3([4.0, 5.0, 6.0])],
+        };
+        // The array is padded to 16 bytes, the next param doesn't fill the last item
+        #[rustfmt::skip]
+        assert_eq!(
+            std140(&[float(0.5), array, float(0.25)]),
+            vec![
+                0.5, 0.0, 0.0, 0.0,
+                1.0, 2.0, 3.0, 0.0,
+                4.0, 5.0, 6.0, 0.0,
+                0.25,
+            ]
+        );
+    }
+}
@@ -36,7 +36,9 @@ impl ShaderLoader {
         self.include_dirs.push(include.to_string());
     }
 
-    /// Load a shader, this will try to guess its type based on the file extension
+    /// Load a shader, this will try to guess its type based on the file extension.
+    /// Errors in the params definitions are reported as a [preprocessor::PreprocessError],
+    /// glsl shaders the preprocessor can't parse are still loaded when they have no params.
     pub fn load_shader<P: AsRef<Path>>(&mut self, path: P) -> Result<(Shader, ShaderSource)> {
         let path = path.as_ref();
         // TODO collect all files necessary to compilation for watch
@@ -56,15 +58,18 @@ impl ShaderLoader {
             }
             Some("glsl") | Some("frag") => {
                 // Preprocess glsl to extract what we need
-                let mut source = fs::read_to_string(path)?;
-                let metadata = if let Ok((metadata, new)) = preprocessor::extract(&source) {
-                    // We found params and transpiled the code
-                    source = new;
-                    Some(metadata)
-                } else {
-                    // No params extracted and source isn't modified
-                    None
-                };
+                let source = fs::read_to_string(path)?;
+                let (metadata, source) =
+                    match preprocessor::extract(&source, path.to_str().unwrap()) {
+                        Ok((metadata, source)) => (Some(metadata), source),
+                        // The parser doesn't support everything shaderc does, shaders without
+                        // params are compiled as is
+                        Err(e) if !preprocessor::has_params_block(&source) => {
+                            debug!("Can't preprocess, compiling as is : {}", e);
+                            (None, source)
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
 
                 self.compile_shader(path.to_str().unwrap(), &source, "main")
                     .map(|it| {
@@ -78,15 +83,18 @@ impl ShaderLoader {
                         )
                     })
             }
-            Some("wgsl") => Ok((
-                // TODO extract data from wgsl
-                Shader {
-                    main: path.to_path_buf(),
-                    sources: vec![path.to_path_buf()],
-                    metadata: None,
-                },
-                ShaderSource::Wgsl(Cow::Owned(fs::read_to_string(path)?)),
-            )),
+            Some("wgsl") => {
+                let source = fs::read_to_string(path)?;
+                let metadata = preprocessor::wgsl::extract(&source, path.to_str().unwrap())?;
+                Ok((
+                    Shader {
+                        main: path.to_path_buf(),
+                        sources: vec![path.to_path_buf()],
+                        metadata: Some(metadata),
+                    },
+                    ShaderSource::Wgsl(Cow::Owned(source)),
+                ))
+            }
             _ => Err(anyhow!("Unsupported shader format !")),
         }
     }
@@ -0,0 +1,200 @@
+//! Keyframe animation of params against the shader time.
+
+/// How a keyframe goes to the next one
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Interpolation {
+    Linear,
+    /// Keep the value until the next keyframe
+    Step,
+    /// Smooth start and end
+    Ease,
+}
+
+impl Interpolation {
+    pub const ALL: [Interpolation; 3] = [
+        Interpolation::Linear,
+        Interpolation::Step,
+        Interpolation::Ease,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Interpolation::Linear => "linear",
+            Interpolation::Step => "step",
+            Interpolation::Ease => "ease",
+        }
+    }
+
+    /// Map the progress between two keyframes, between 0 and 1
+    fn apply(&self, t: f32) -> f32 {
+        match self {
+            Interpolation::Linear => t,
+            Interpolation::Step => 0.0,
+            Interpolation::Ease => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Keyframe {
+    /// Time on the timeline in sec
+    pub time: f32,
+    /// Components of the param value, see [crate::shader::Slider::components]
+    pub value: Vec<f64>,
+    /// Interpolation toward the next keyframe
+    pub interpolation: Interpolation,
+}
+
+/// Value of a param at the given time, keyframes must be sorted by time
+pub fn evaluate(keyframes: &[Keyframe], time: f32) -> Option<Vec<f64>> {
+    let next = keyframes.iter().position(|it| it.time > time);
+    match next {
+        None => keyframes.last().map(|it| it.value.clone()),
+        Some(0) => Some(keyframes[0].value.clone()),
+        Some(i) => {
+            let (a, b) = (&keyframes[i - 1], &keyframes[i]);
+            let t = a.interpolation.apply((time - a.time) / (b.time - a.time)) as f64;
+            Some(
+                a.value
+                    .iter()
+                    .zip(b.value.iter())
+                    .map(|(a, b)| a + (b - a) * t)
+                    .collect(),
+            )
+        }
+    }
+}
+
+/// Settings of the timeline panel
+pub struct Timeline {
+    /// Length of the timeline in sec
+    pub duration: f32,
+    /// Wrap the shader time around the duration, for animated loops
+    pub looping: bool,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self {
+            duration: 10.0,
+            looping: true,
+        }
+    }
+}
+
+impl Timeline {
+    /// Position on the timeline for the given shader time
+    pub fn local_time(&self, time: f32) -> f32 {
+        if self.looping && self.duration > 0.0 {
+            time % self.duration
+        } else {
+            time
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyframe(time: f32, value: f64, interpolation: Interpolation) -> Keyframe {
+        Keyframe {
+            time,
+            value: vec![value],
+            interpolation,
+        }
+    }
+
+    fn value_at(keyframes: &[Keyframe], time: f32) -> f64 {
+        evaluate(keyframes, time).unwrap()[0]
+    }
+
+    #[test]
+    fn no_keyframes() {
+        assert_eq!(evaluate(&[], 1.0), None);
+    }
+
+    #[test]
+    fn flat_outside_keyframes() {
+        let keyframes = [
+            keyframe(1.0, 10.0, Interpolation::Linear),
+            keyframe(2.0, 20.0, Interpolation::Linear),
+        ];
+        assert_eq!(value_at(&keyframes, 0.0), 10.0);
+        assert_eq!(value_at(&keyframes, 3.0), 20.0);
+    }
+
+    #[test]
+    fn at_keyframes() {
+        for interpolation in Interpolation::ALL.iter() {
+            let keyframes = [
+                keyframe(0.0, 1.0, *interpolation),
+                keyframe(1.0, 3.0, *interpolation),
+                keyframe(2.0, -1.0, *interpolation),
+            ];
+            assert_eq!(value_at(&keyframes, 0.0), 1.0);
+            assert_eq!(value_at(&keyframes, 1.0), 3.0);
+            assert_eq!(value_at(&keyframes, 2.0), -1.0);
+        }
+    }
+
+    #[test]
+    fn linear() {
+        let keyframes = [
+            keyframe(0.0, 0.0, Interpolation::Linear),
+            keyframe(2.0, 10.0, Interpolation::Linear),
+        ];
+        assert_eq!(value_at(&keyframes, 0.5), 2.5);
+        assert_eq!(value_at(&keyframes, 1.0), 5.0);
+    }
+
+    #[test]
+    fn step() {
+        let keyframes = [
+            keyframe(0.0, 0.0, Interpolation::Step),
+            keyframe(2.0, 10.0, Interpolation::Linear),
+        ];
+        assert_eq!(value_at(&keyframes, 1.0), 0.0);
+        assert_eq!(value_at(&keyframes, 1.99), 0.0);
+    }
+
+    #[test]
+    fn ease() {
+        let keyframes = [
+            keyframe(0.0, 0.0, Interpolation::Ease),
+            keyframe(1.0, 1.0, Interpolation::Linear),
+        ];
+        // Symmetric around the middle, slower than linear near the keyframes
+        assert_eq!(value_at(&keyframes, 0.5), 0.5);
+        assert!(value_at(&keyframes, 0.25) < 0.25);
+        assert!(value_at(&keyframes, 0.75) > 0.75);
+    }
+
+    #[test]
+    fn interpolation_of_the_previous_keyframe() {
+        let keyframes = [
+            keyframe(0.0, 0.0, Interpolation::Linear),
+            keyframe(1.0, 1.0, Interpolation::Step),
+            keyframe(2.0, 2.0, Interpolation::Linear),
+        ];
+        assert_eq!(value_at(&keyframes, 0.5), 0.5);
+        assert_eq!(value_at(&keyframes, 1.5), 1.0);
+    }
+
+    #[test]
+    fn every_component() {
+        let keyframes = [
+            Keyframe {
+                time: 0.0,
+                value: vec![0.0, 10.0],
+                interpolation: Interpolation::Linear,
+            },
+            Keyframe {
+                time: 1.0,
+                value: vec![1.0, 20.0],
+                interpolation: Interpolation::Linear,
+            },
+        ];
+        assert_eq!(evaluate(&keyframes, 0.5), Some(vec![0.5, 15.0]));
+    }
+}